@@ -0,0 +1,301 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::iter::{Product, Sum};
+use std::ops::{Add, AddAssign, Mul};
+use std::str::FromStr;
+
+/// Minimal arbitrary-precision unsigned integer, enough for the puzzles that
+/// outgrow `u128`: addition, multiplication, comparison and decimal I/O.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct BigUint {
+    // Little-endian base 2^32 limbs without trailing zeros; zero is empty.
+    limbs: Vec<u32>,
+}
+
+impl BigUint {
+    pub fn zero() -> Self {
+        Self::default()
+    }
+
+    pub fn one() -> Self {
+        Self::from(1u128)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    /// Number of significant bits; zero for zero.
+    pub fn bits(&self) -> u64 {
+        match self.limbs.last() {
+            Some(top) => self.limbs.len() as u64 * 32 - top.leading_zeros() as u64,
+            None => 0,
+        }
+    }
+
+    pub fn to_u128(&self) -> Option<u128> {
+        if self.limbs.len() > 4 {
+            return None;
+        }
+        Some(
+            self.limbs
+                .iter()
+                .rev()
+                .fold(0u128, |acc, &l| (acc << 32) | l as u128),
+        )
+    }
+
+    fn normalize(&mut self) {
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+    }
+
+    fn mul_small_add(&mut self, mul: u32, add: u32) {
+        let mut carry = add as u64;
+        for limb in self.limbs.iter_mut() {
+            let v = *limb as u64 * mul as u64 + carry;
+            *limb = v as u32;
+            carry = v >> 32;
+        }
+        if carry > 0 {
+            self.limbs.push(carry as u32);
+        }
+        self.normalize();
+    }
+
+    /// Divides in place by `div`, returning the remainder.
+    fn div_small(&mut self, div: u32) -> u32 {
+        let mut rem = 0u64;
+        for limb in self.limbs.iter_mut().rev() {
+            let v = (rem << 32) | *limb as u64;
+            *limb = (v / div as u64) as u32;
+            rem = v % div as u64;
+        }
+        self.normalize();
+        rem as u32
+    }
+}
+
+impl From<u128> for BigUint {
+    fn from(mut n: u128) -> Self {
+        let mut limbs = Vec::new();
+        while n > 0 {
+            limbs.push(n as u32);
+            n >>= 32;
+        }
+        Self { limbs }
+    }
+}
+
+impl From<u64> for BigUint {
+    fn from(n: u64) -> Self {
+        Self::from(n as u128)
+    }
+}
+
+impl From<usize> for BigUint {
+    fn from(n: usize) -> Self {
+        Self::from(n as u128)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseBigUintError;
+
+impl fmt::Display for ParseBigUintError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid decimal digit string")
+    }
+}
+
+impl std::error::Error for ParseBigUintError {}
+
+impl FromStr for BigUint {
+    type Err = ParseBigUintError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(ParseBigUintError);
+        }
+        let mut n = BigUint::zero();
+        for ch in s.chars() {
+            let digit = ch.to_digit(10).ok_or(ParseBigUintError)?;
+            n.mul_small_add(10, digit);
+        }
+        Ok(n)
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl AddAssign<&BigUint> for BigUint {
+    fn add_assign(&mut self, rhs: &BigUint) {
+        if self.limbs.len() < rhs.limbs.len() {
+            self.limbs.resize(rhs.limbs.len(), 0);
+        }
+        let mut carry = 0u64;
+        for (i, limb) in self.limbs.iter_mut().enumerate() {
+            let v = *limb as u64 + *rhs.limbs.get(i).unwrap_or(&0) as u64 + carry;
+            *limb = v as u32;
+            carry = v >> 32;
+        }
+        if carry > 0 {
+            self.limbs.push(carry as u32);
+        }
+    }
+}
+
+impl Add<&BigUint> for &BigUint {
+    type Output = BigUint;
+
+    fn add(self, rhs: &BigUint) -> BigUint {
+        let mut out = self.clone();
+        out += rhs;
+        out
+    }
+}
+
+impl Add for BigUint {
+    type Output = BigUint;
+
+    fn add(mut self, rhs: BigUint) -> BigUint {
+        self += &rhs;
+        self
+    }
+}
+
+impl Mul<&BigUint> for &BigUint {
+    type Output = BigUint;
+
+    fn mul(self, rhs: &BigUint) -> BigUint {
+        if self.is_zero() || rhs.is_zero() {
+            return BigUint::zero();
+        }
+        let mut limbs = vec![0u32; self.limbs.len() + rhs.limbs.len()];
+        for (i, &a) in self.limbs.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, &b) in rhs.limbs.iter().enumerate() {
+                let v = a as u64 * b as u64 + limbs[i + j] as u64 + carry;
+                limbs[i + j] = v as u32;
+                carry = v >> 32;
+            }
+            limbs[i + rhs.limbs.len()] = carry as u32;
+        }
+        let mut out = BigUint { limbs };
+        out.normalize();
+        out
+    }
+}
+
+impl Mul for BigUint {
+    type Output = BigUint;
+
+    fn mul(self, rhs: BigUint) -> BigUint {
+        &self * &rhs
+    }
+}
+
+impl Sum for BigUint {
+    fn sum<I: Iterator<Item = BigUint>>(iter: I) -> Self {
+        iter.fold(BigUint::zero(), |acc, n| acc + n)
+    }
+}
+
+impl Product for BigUint {
+    fn product<I: Iterator<Item = BigUint>>(iter: I) -> Self {
+        iter.fold(BigUint::one(), |acc, n| acc * n)
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_zero() {
+            return f.pad("0");
+        }
+        // Peel off nine decimal digits at a time.
+        let mut n = self.clone();
+        let mut chunks = Vec::new();
+        while !n.is_zero() {
+            chunks.push(n.div_small(1_000_000_000));
+        }
+        let mut s = chunks.last().unwrap().to_string();
+        for chunk in chunks.iter().rev().skip(1) {
+            s.push_str(&format!("{:09}", chunk));
+        }
+        f.pad(&s)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::bigint::BigUint;
+
+    #[test]
+    fn test_round_trip() {
+        for n in [
+            0u128,
+            1,
+            9,
+            10,
+            999_999_999,
+            1_000_000_000,
+            u64::MAX as u128,
+            u128::MAX,
+        ] {
+            let big = BigUint::from(n);
+            assert_eq!(big.to_string(), n.to_string());
+            assert_eq!(big.to_u128(), Some(n));
+            assert_eq!(n.to_string().parse::<BigUint>(), Ok(big));
+        }
+        assert!("12a".parse::<BigUint>().is_err());
+        assert!("".parse::<BigUint>().is_err());
+    }
+
+    #[test]
+    fn test_beyond_u128() {
+        let max = BigUint::from(u128::MAX);
+        let sum = &max + &BigUint::one();
+        assert_eq!(sum.to_string(), "340282366920938463463374607431768211456");
+        assert_eq!(sum.to_u128(), None);
+        assert_eq!(sum.bits(), 129);
+        assert_eq!(max.bits(), 128);
+        assert_eq!(BigUint::zero().bits(), 0);
+        assert!(sum > max);
+
+        let square = &max * &max;
+        assert_eq!(
+            square.to_string(),
+            "115792089237316195423570985008687907852589419931798687112530834793049593217025"
+        );
+        let digits = "1".repeat(60);
+        assert_eq!(digits.parse::<BigUint>().unwrap().to_string(), digits);
+    }
+
+    #[test]
+    fn test_sum_product_and_order() {
+        let nums: Vec<BigUint> = (1..=30u128).map(BigUint::from).collect();
+        let factorial: BigUint = nums.iter().cloned().product();
+        assert_eq!(factorial.to_string(), "265252859812191058636308480000000");
+        let total: BigUint = nums.into_iter().sum();
+        assert_eq!(total, BigUint::from(465u128));
+        assert!(BigUint::zero() < BigUint::one());
+        assert_eq!(
+            BigUint::from(5u128).max(BigUint::from(7u128)),
+            BigUint::from(7u128)
+        );
+    }
+}
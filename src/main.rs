@@ -1,3 +1,4 @@
+mod bigint;
 mod p_01;
 mod p_02;
 mod p_02_fp;
@@ -6,8 +7,12 @@ mod p_04;
 mod p_05;
 mod p_06;
 mod p_07;
+mod p_07_optics;
 mod p_08;
 mod visualizer_p04;
+mod visualizer_p07;
+mod visualizer_p08;
+mod viz;
 
 fn main() {
     println!("AOC 2025 - Advent of Code Solutions");
@@ -26,20 +31,84 @@ fn main() {
 
         let cell_size = if input_path.contains("sample") { 20 } else { 4 };
 
-        visualizer_p04::visualize(
+        visualizer_p04::visualize_with(
             input_path,
             Some("rust_evolution.gif"),
             Some("rust_summary.png"),
             cell_size,
             50, // 500ms delay per frame
             1000,
+            visualizer_p04::Pipeline::Auto,
+            args.iter().any(|a| a == "--compare"),
         )
         .expect("Visualization failed");
+    } else if args.len() > 1 && args[1] == "visualize-p07" {
+        println!("\n=== Running P07 Visualizer ===\n");
+
+        let input_path = if args.len() > 2 && args[2] == "sample" {
+            "src/p_07_sample.txt"
+        } else {
+            "src/p_07.txt"
+        };
+
+        let cell_size = if input_path.contains("sample") { 20 } else { 4 };
+
+        visualizer_p07::visualize(
+            input_path,
+            Some("rust_p07_heatmap.png"),
+            Some("rust_p07_beams.gif"),
+            cell_size,
+            10, // 100ms delay per row
+        )
+        .expect("Visualization failed");
+    } else if args.len() > 1 && args[1] == "visualize-p08" {
+        println!("\n=== Running P08 Visualizer ===\n");
+
+        let input_path = if args.len() > 2 && args[2] == "sample" {
+            "src/p_08_sample.txt"
+        } else {
+            "src/p_08.txt"
+        };
+
+        visualizer_p08::visualize(
+            input_path,
+            visualizer_p08::Projection::Isometric,
+            Some("rust_p08_clusters.png"),
+            Some("rust_p08_kruskal.gif"),
+            600,
+            if input_path.contains("sample") { 4 } else { 20 },
+            10,  // 100ms delay per frame
+            200, // frames at most
+        )
+        .expect("Visualization failed");
+    } else if args.len() > 2 && args[1] == "optics" {
+        println!("\n=== Simulating P07 optics layout ===\n");
+
+        let config = p_07_optics::OpticsConfig {
+            splitter_fan_out: args.get(3).and_then(|n| n.parse().ok()).unwrap_or(2),
+        };
+        if let Err(e) = p_07_optics::run(&args[2], config) {
+            println!("Simulation failed: {}", e);
+        }
+    } else if args.len() > 1 && args[1] == "bench-p08" {
+        println!("\n=== Benchmarking P08 MST strategies ===\n");
+
+        let points = args.get(2).and_then(|n| n.parse().ok()).unwrap_or(2000);
+        if let Err(e) = p_08::benchmark_mst(points) {
+            println!("Benchmark failed: {}", e);
+        }
     } else {
         println!("\nUsage:");
         println!("  cargo run                    - Display this message");
         println!("  cargo run visualize          - Visualize full input (p_04.txt)");
         println!("  cargo run visualize sample   - Visualize sample input");
+        println!("  cargo run visualize --compare - Also report the GIF size with full frames");
+        println!("  cargo run visualize-p07      - Render beam paths for p_07.txt");
+        println!("  cargo run visualize-p07 sample - Render beam paths for the sample");
+        println!("  cargo run optics <file> [k]  - Simulate a p_07 layout with mirrors etc.");
+        println!("  cargo run visualize-p08      - Render clusters and MST for p_08.txt");
+        println!("  cargo run visualize-p08 sample - Render clusters and MST for the sample");
+        println!("  cargo run bench-p08 [n]      - Time the p_08 MST strategies on n points");
         println!("  cargo test                   - Run all tests including visualizer tests");
     }
 }
@@ -1,23 +1,48 @@
-use std::fs::read_to_string;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+
+enum InputLine {
+    Range([u64; 2]),
+    Id(u64),
+}
+
+fn parse_line(line: &str) -> Option<InputLine> {
+    if line.contains('-') {
+        let splits = line.split_once("-").expect("Invalid input line");
+        let (start, end) = (
+            splits.0.parse::<u64>().expect("Unable to parse number"),
+            splits.1.parse::<u64>().expect("Unable to parse number"),
+        );
+        Some(InputLine::Range([start, end]))
+    } else if line.is_empty() {
+        None
+    } else {
+        Some(InputLine::Id(
+            line.parse::<u64>().expect("Unable to parse ID to check"),
+        ))
+    }
+}
+
+/// Yields parsed lines one at a time without loading the whole file.
+fn stream_input(path: &str) -> impl Iterator<Item = InputLine> {
+    let file = File::open(path).expect("Unable to read file");
+    BufReader::new(file)
+        .lines()
+        .filter_map(|line| parse_line(&line.expect("Unable to read line")))
+}
 
 fn read_input(path: &str) -> (Vec<[u64; 2]>, Vec<u64>) {
     let mut ranges = Vec::new();
     let mut ids = Vec::new();
-    for line in read_to_string(path).expect("Unable to read file").lines() {
-        if line.contains('-') {
-            let splits = line.split_once("-").expect("Invalid input line");
-            let (start, end) = (
-                splits.0.parse::<u64>().expect("Unable to parse number"),
-                splits.1.parse::<u64>().expect("Unable to parse number"),
-            );
-            ranges.push([start, end]);
-        } else if line.eq("") {
-            continue;
-        } else {
-            ids.push(line.parse::<u64>().expect("Unable to parse ID to check"));
+    for line in stream_input(path) {
+        match line {
+            InputLine::Range(r) => ranges.push(r),
+            InputLine::Id(id) => ids.push(id),
         }
     }
-    return (ranges, ids);
+    (ranges, ids)
 }
 
 fn part1(path: &str) -> u64 {
@@ -32,15 +57,74 @@ fn part1(path: &str) -> u64 {
     ans
 }
 
-fn merge_overlapping_intervals(mut ranges: Vec<[u64; 2]>) -> Vec<[u64; 2]> {
+/// How the two endpoints of a `[start, end]` pair are interpreted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeKind {
+    /// `start..=end`, the puzzle's native format.
+    Closed,
+    /// `start..end`, the end is excluded.
+    HalfOpen,
+}
+
+impl RangeKind {
+    fn is_empty(self, r: [u64; 2]) -> bool {
+        match self {
+            RangeKind::Closed => r[0] > r[1],
+            RangeKind::HalfOpen => r[0] >= r[1],
+        }
+    }
+
+    fn contains(self, r: [u64; 2], id: u64) -> bool {
+        match self {
+            RangeKind::Closed => r[0] <= id && id <= r[1],
+            RangeKind::HalfOpen => r[0] <= id && id < r[1],
+        }
+    }
+
+    /// Number of integers covered by `r`, widened so `[0, u64::MAX]` fits.
+    fn len(self, r: [u64; 2]) -> u128 {
+        if self.is_empty(r) {
+            return 0;
+        }
+        let span = (r[1] - r[0]) as u128;
+        match self {
+            RangeKind::Closed => span + 1,
+            RangeKind::HalfOpen => span,
+        }
+    }
+
+    /// Whether `next` (which starts at or after `last`) should be folded into `last`.
+    fn joins(self, last: [u64; 2], next: [u64; 2], coalesce_adjacent: bool) -> bool {
+        match self {
+            RangeKind::Closed => {
+                next[0] <= last[1] || (coalesce_adjacent && next[0] - last[1] == 1)
+            }
+            RangeKind::HalfOpen => next[0] < last[1] || (coalesce_adjacent && next[0] == last[1]),
+        }
+    }
+}
+
+fn merge_overlapping_intervals(ranges: Vec<[u64; 2]>) -> Vec<[u64; 2]> {
+    merge_intervals(ranges, RangeKind::Closed, false)
+}
+
+/// Sorts and merges `ranges`, dropping empty ones. With `coalesce_adjacent`,
+/// ranges that touch without overlapping (`[1,3]` and `[4,6]` when closed)
+/// are merged as well.
+fn merge_intervals(
+    mut ranges: Vec<[u64; 2]>,
+    kind: RangeKind,
+    coalesce_adjacent: bool,
+) -> Vec<[u64; 2]> {
+    ranges.retain(|r| !kind.is_empty(*r));
     ranges.sort_by(|a, b| a[0].cmp(&b[0]).then_with(|| a[1].cmp(&b[1])));
     let mut merged: Vec<[u64; 2]> = Vec::new();
     for r in ranges {
-        if let Some(last) = merged.last_mut() {
-            if r[0] <= last[1] {
-                last[1] = last[1].max(r[1]);
-                continue;
-            }
+        if let Some(last) = merged.last_mut()
+            && kind.joins(*last, r, coalesce_adjacent)
+        {
+            last[1] = last[1].max(r[1]);
+            continue;
         }
         merged.push(r);
     }
@@ -48,31 +132,421 @@ fn merge_overlapping_intervals(mut ranges: Vec<[u64; 2]>) -> Vec<[u64; 2]> {
 }
 
 fn binary_search_ranges(merged: &[[u64; 2]], id: u64) -> bool {
+    binary_search_ranges_kind(merged, id, RangeKind::Closed)
+}
+
+fn binary_search_ranges_kind(merged: &[[u64; 2]], id: u64, kind: RangeKind) -> bool {
     merged
         .binary_search_by(|range| {
-            if id < range[0] {
+            if kind.contains(*range, id) {
+                std::cmp::Ordering::Equal
+            } else if id < range[0] {
                 std::cmp::Ordering::Greater
-            } else if id > range[1] {
-                std::cmp::Ordering::Less
             } else {
-                std::cmp::Ordering::Equal
+                std::cmp::Ordering::Less
             }
         })
         .is_ok()
 }
 
-fn part_2(path: &str) -> u64 {
-    let (ranges, ids) = read_input(path);
+fn total_len(merged: &[[u64; 2]], kind: RangeKind) -> u128 {
+    merged.iter().map(|r| kind.len(*r)).sum()
+}
+
+fn part_2(path: &str) -> u128 {
+    let (ranges, _) = read_input(path);
     let merged_ranges = merge_overlapping_intervals(ranges);
+    total_len(&merged_ranges, RangeKind::Closed)
+}
+
+/// Result of looking up a single ingredient ID.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdMatch {
+    pub id: u64,
+    /// The merged interval containing `id`, if it is fresh.
+    pub merged: Option<[u64; 2]>,
+    /// Every original range containing `id`, sorted by start then end.
+    pub sources: Vec<[u64; 2]>,
+}
+
+/// Answers all `ids` in one sweep over the sorted ranges instead of a
+/// binary search per ID. Results come back in the order of `ids`.
+fn query_ids(ranges: &[[u64; 2]], ids: &[u64]) -> Vec<IdMatch> {
+    let merged = merge_overlapping_intervals(ranges.to_vec());
+    let mut sorted_ranges = ranges.to_vec();
+    sorted_ranges.retain(|r| r[0] <= r[1]);
+    sorted_ranges.sort_unstable();
+
+    let mut order: Vec<usize> = (0..ids.len()).collect();
+    order.sort_by_key(|&i| ids[i]);
+
+    let mut results: Vec<Option<IdMatch>> = vec![None; ids.len()];
+    // Ranges that have started, keyed by end so expired ones pop first.
+    let mut active: BinaryHeap<Reverse<(u64, u64)>> = BinaryHeap::new();
+    let mut next_range = 0;
+    let mut merged_idx = 0;
+
+    for i in order {
+        let id = ids[i];
+        while next_range < sorted_ranges.len() && sorted_ranges[next_range][0] <= id {
+            let [start, end] = sorted_ranges[next_range];
+            active.push(Reverse((end, start)));
+            next_range += 1;
+        }
+        while let Some(Reverse((end, _))) = active.peek() {
+            if *end >= id {
+                break;
+            }
+            active.pop();
+        }
+        while merged_idx < merged.len() && merged[merged_idx][1] < id {
+            merged_idx += 1;
+        }
+
+        let matched = merged
+            .get(merged_idx)
+            .copied()
+            .filter(|r| RangeKind::Closed.contains(*r, id));
+        let mut sources: Vec<[u64; 2]> = active
+            .iter()
+            .map(|Reverse((end, start))| [*start, *end])
+            .collect();
+        sources.sort_unstable();
+
+        results[i] = Some(IdMatch {
+            id,
+            merged: matched,
+            sources,
+        });
+    }
+
+    results.into_iter().map(|m| m.unwrap()).collect()
+}
+
+/// Writes one CSV row per match: `id,fresh,merged_start,merged_end,sources`,
+/// where `sources` is a `;`-separated list of `start-end` ranges.
+fn write_matches_csv<W: Write>(matches: &[IdMatch], out: &mut W) -> std::io::Result<()> {
+    writeln!(out, "id,fresh,merged_start,merged_end,sources")?;
+    for m in matches {
+        let (start, end) = match m.merged {
+            Some([s, e]) => (s.to_string(), e.to_string()),
+            None => (String::new(), String::new()),
+        };
+        let sources: Vec<String> = m
+            .sources
+            .iter()
+            .map(|r| format!("{}-{}", r[0], r[1]))
+            .collect();
+        writeln!(
+            out,
+            "{},{},{},{},{}",
+            m.id,
+            m.merged.is_some(),
+            start,
+            end,
+            sources.join(";")
+        )?;
+    }
+    Ok(())
+}
+
+fn part1_audit(path: &str, csv_path: &str) -> Result<u64, Box<dyn std::error::Error>> {
+    let (ranges, ids) = read_input(path);
+    let matches = query_ids(&ranges, &ids);
+    let mut file = std::fs::File::create(csv_path)?;
+    write_matches_csv(&matches, &mut file)?;
+    Ok(matches.iter().filter(|m| m.merged.is_some()).count() as u64)
+}
+
+type Link = Option<Box<TreeNode>>;
+
+#[derive(Debug)]
+struct TreeNode {
+    range: (u64, u64),
+    count: usize,
+    priority: u64,
+    // Largest end in this subtree, so stabbing queries can skip subtrees
+    // that end before the point.
+    max_end: u64,
+    left: Link,
+    right: Link,
+}
+
+impl TreeNode {
+    fn new(range: (u64, u64)) -> Box<Self> {
+        // A fixed hash of the range keeps the treap balanced in expectation
+        // while staying deterministic.
+        let mut priority = range.0 ^ range.1.rotate_left(32) ^ 0x9E37_79B9_7F4A_7C15;
+        priority = (priority ^ (priority >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        priority = (priority ^ (priority >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        Box::new(Self {
+            range,
+            count: 1,
+            priority: priority ^ (priority >> 31),
+            max_end: range.1,
+            left: None,
+            right: None,
+        })
+    }
+
+    fn update(&mut self) {
+        self.max_end = [&self.left, &self.right]
+            .into_iter()
+            .flatten()
+            .map(|child| child.max_end)
+            .fold(self.range.1, u64::max);
+    }
+}
+
+/// Multiset of closed ranges ordered by `(start, end)`: a treap augmented
+/// with the maximum end of every subtree.
+#[derive(Debug, Default)]
+struct IntervalTree {
+    root: Link,
+}
+
+impl IntervalTree {
+    /// Splits into the nodes for which `goes_left` holds and the rest.
+    /// `goes_left` must be monotone in the key order.
+    fn split(node: Link, goes_left: &impl Fn((u64, u64)) -> bool) -> (Link, Link) {
+        let Some(mut node) = node else {
+            return (None, None);
+        };
+        if goes_left(node.range) {
+            let (mid, right) = Self::split(node.right.take(), goes_left);
+            node.right = mid;
+            node.update();
+            (Some(node), right)
+        } else {
+            let (left, mid) = Self::split(node.left.take(), goes_left);
+            node.left = mid;
+            node.update();
+            (left, Some(node))
+        }
+    }
+
+    /// Joins two treaps where every key of `left` is smaller than `right`'s.
+    fn merge(left: Link, right: Link) -> Link {
+        match (left, right) {
+            (None, node) | (node, None) => node,
+            (Some(mut l), Some(mut r)) => {
+                if l.priority >= r.priority {
+                    l.right = Self::merge(l.right.take(), Some(r));
+                    l.update();
+                    Some(l)
+                } else {
+                    r.left = Self::merge(Some(l), r.left.take());
+                    r.update();
+                    Some(r)
+                }
+            }
+        }
+    }
+
+    fn find_mut(&mut self, range: (u64, u64)) -> Option<&mut TreeNode> {
+        let mut node = self.root.as_deref_mut();
+        while let Some(n) = node {
+            node = match range.cmp(&n.range) {
+                std::cmp::Ordering::Less => n.left.as_deref_mut(),
+                std::cmp::Ordering::Greater => n.right.as_deref_mut(),
+                std::cmp::Ordering::Equal => return Some(n),
+            };
+        }
+        None
+    }
+
+    fn insert(&mut self, range: (u64, u64)) {
+        if let Some(node) = self.find_mut(range) {
+            node.count += 1;
+            return;
+        }
+        let (left, right) = Self::split(self.root.take(), &|r| r < range);
+        self.root = Self::merge(Self::merge(left, Some(TreeNode::new(range))), right);
+    }
+
+    /// Removes one copy of `range`. Returns `false` if it is not present.
+    fn remove(&mut self, range: (u64, u64)) -> bool {
+        match self.find_mut(range) {
+            None => return false,
+            Some(node) if node.count > 1 => {
+                node.count -= 1;
+                return true;
+            }
+            Some(_) => {}
+        }
+        let (left, rest) = Self::split(self.root.take(), &|r| r < range);
+        let (_, right) = Self::split(rest, &|r| r <= range);
+        self.root = Self::merge(left, right);
+        true
+    }
+
+    /// Every range containing `id`, with repeats, in key order.
+    fn stab(&self, id: u64) -> Vec<[u64; 2]> {
+        fn visit(node: &Link, id: u64, out: &mut Vec<[u64; 2]>) {
+            let Some(node) = node else { return };
+            if node.max_end < id {
+                return;
+            }
+            visit(&node.left, id, out);
+            if node.range.0 > id {
+                // Everything to the right starts later still.
+                return;
+            }
+            if node.range.1 >= id {
+                out.extend(std::iter::repeat_n(
+                    [node.range.0, node.range.1],
+                    node.count,
+                ));
+            }
+            visit(&node.right, id, out);
+        }
+        let mut out = Vec::new();
+        visit(&self.root, id, &mut out);
+        out
+    }
+
+    /// Distinct ranges starting in `from..=to`, in key order.
+    fn starting_in(&self, from: u64, to: u64) -> Vec<[u64; 2]> {
+        fn visit(node: &Link, from: u64, to: u64, out: &mut Vec<[u64; 2]>) {
+            let Some(node) = node else { return };
+            let (start, end) = node.range;
+            if start >= from {
+                visit(&node.left, from, to, out);
+            }
+            if (from..=to).contains(&start) {
+                out.push([start, end]);
+            }
+            if start <= to {
+                visit(&node.right, from, to, out);
+            }
+        }
+        let mut out = Vec::new();
+        visit(&self.root, from, to, &mut out);
+        out
+    }
+}
+
+/// Dynamic set of closed ranges supporting insert/remove and point queries
+/// without re-sorting. Originals are kept in an interval tree so removals can
+/// re-split the merged interval they belonged to and stabbing queries only
+/// visit ranges that can contain the point.
+#[derive(Debug, Default)]
+pub struct RangeIndex {
+    originals: IntervalTree,
+    // start -> end of disjoint merged intervals
+    merged: BTreeMap<u64, u64>,
+    covered: u128,
+}
+
+impl RangeIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, range: [u64; 2]) {
+        let [mut start, mut end] = range;
+        if start > end {
+            return;
+        }
+        self.originals.insert((start, end));
+
+        let overlapping: Vec<(u64, u64)> = self
+            .merged
+            .range(..=end)
+            .rev()
+            .take_while(|(_, e)| **e >= start)
+            .map(|(s, e)| (*s, *e))
+            .collect();
+        for (s, e) in overlapping {
+            self.merged.remove(&s);
+            self.covered -= RangeKind::Closed.len([s, e]);
+            start = start.min(s);
+            end = end.max(e);
+        }
+        self.merged.insert(start, end);
+        self.covered += RangeKind::Closed.len([start, end]);
+    }
+
+    /// Removes one copy of `range`. Returns `false` if it was never inserted.
+    pub fn remove(&mut self, range: [u64; 2]) -> bool {
+        if !self.originals.remove((range[0], range[1])) {
+            return false;
+        }
+
+        let (ms, me) = self
+            .merged
+            .range(..=range[0])
+            .next_back()
+            .map(|(s, e)| (*s, *e))
+            .expect("Removed range must lie in a merged interval");
+        self.merged.remove(&ms);
+        self.covered -= RangeKind::Closed.len([ms, me]);
+
+        // Every original starting inside the old interval belonged to it.
+        let members = self.originals.starting_in(ms, me);
+        for r in merge_overlapping_intervals(members) {
+            self.merged.insert(r[0], r[1]);
+            self.covered += RangeKind::Closed.len(r);
+        }
+        true
+    }
+
+    pub fn contains(&self, id: u64) -> bool {
+        self.merged
+            .range(..=id)
+            .next_back()
+            .is_some_and(|(_, e)| *e >= id)
+    }
+
+    /// All original ranges containing `id` (stabbing query), sorted by start
+    /// then end.
+    pub fn stab(&self, id: u64) -> Vec<[u64; 2]> {
+        self.originals.stab(id)
+    }
+
+    pub fn covered_len(&self) -> u128 {
+        self.covered
+    }
+
+    pub fn merged(&self) -> Vec<[u64; 2]> {
+        self.merged.iter().map(|(s, e)| [*s, *e]).collect()
+    }
+}
+
+/// Like `part1`, but builds the index while the file is read. IDs are checked
+/// against the ranges seen so far, which matches the input's ranges-first layout.
+fn part1_incremental(path: &str) -> u64 {
+    let mut index = RangeIndex::new();
     let mut ans = 0;
-    for r in merged_ranges {
-        ans += r[1] - r[0] + 1;
+    for line in stream_input(path) {
+        match line {
+            InputLine::Range(r) => index.insert(r),
+            InputLine::Id(id) => {
+                if index.contains(id) {
+                    ans += 1;
+                }
+            }
+        }
     }
     ans
 }
 
+fn part_2_incremental(path: &str) -> u128 {
+    let mut index = RangeIndex::new();
+    for line in stream_input(path) {
+        if let InputLine::Range(r) = line {
+            index.insert(r);
+        }
+    }
+    index.covered_len()
+}
+
 mod test {
-    use crate::p_05::{part_2, part1, read_input};
+    use crate::p_05::{
+        IdMatch, RangeIndex, RangeKind, binary_search_ranges_kind, merge_intervals, part_2,
+        part_2_incremental, part1, part1_audit, part1_incremental, query_ids, read_input,
+        total_len, write_matches_csv,
+    };
 
     #[test]
     fn test_basic() {
@@ -97,4 +571,245 @@ mod test {
         const PATH: &str = "/Users/saranshagarwal/Code/aoc2025/src/p_05.txt";
         println!("The answer is {}", part_2(PATH));
     }
+
+    #[test]
+    fn test_adjacent_coalescing() {
+        let ranges = vec![[4, 6], [1, 3], [8, 9]];
+        assert_eq!(
+            merge_intervals(ranges.clone(), RangeKind::Closed, false),
+            vec![[1, 3], [4, 6], [8, 9]]
+        );
+        assert_eq!(
+            merge_intervals(ranges, RangeKind::Closed, true),
+            vec![[1, 6], [8, 9]]
+        );
+
+        let half_open = vec![[1, 4], [4, 7], [8, 9]];
+        assert_eq!(
+            merge_intervals(half_open.clone(), RangeKind::HalfOpen, false),
+            half_open
+        );
+        assert_eq!(
+            merge_intervals(half_open, RangeKind::HalfOpen, true),
+            vec![[1, 7], [8, 9]]
+        );
+    }
+
+    #[test]
+    fn test_half_open_semantics() {
+        let merged = merge_intervals(vec![[3, 5], [5, 5], [10, 20]], RangeKind::HalfOpen, false);
+        assert_eq!(merged, vec![[3, 5], [10, 20]]);
+        assert!(binary_search_ranges_kind(&merged, 4, RangeKind::HalfOpen));
+        assert!(!binary_search_ranges_kind(&merged, 5, RangeKind::HalfOpen));
+        assert!(!binary_search_ranges_kind(&merged, 20, RangeKind::HalfOpen));
+        assert!(binary_search_ranges_kind(&merged, 20, RangeKind::Closed));
+        assert_eq!(total_len(&merged, RangeKind::HalfOpen), 12);
+    }
+
+    #[test]
+    fn test_numeric_boundaries() {
+        let full = merge_intervals(vec![[0, u64::MAX]], RangeKind::Closed, true);
+        assert_eq!(total_len(&full, RangeKind::Closed), u64::MAX as u128 + 1);
+        assert!(binary_search_ranges_kind(
+            &full,
+            u64::MAX,
+            RangeKind::Closed
+        ));
+
+        let merged = merge_intervals(
+            vec![[u64::MAX, u64::MAX], [u64::MAX - 1, u64::MAX - 1], [0, 0]],
+            RangeKind::Closed,
+            true,
+        );
+        assert_eq!(merged, vec![[0, 0], [u64::MAX - 1, u64::MAX]]);
+        assert_eq!(total_len(&merged, RangeKind::Closed), 3);
+
+        let half_open = merge_intervals(vec![[0, u64::MAX]], RangeKind::HalfOpen, true);
+        assert_eq!(total_len(&half_open, RangeKind::HalfOpen), u64::MAX as u128);
+        assert!(!binary_search_ranges_kind(
+            &half_open,
+            u64::MAX,
+            RangeKind::HalfOpen
+        ));
+    }
+
+    #[test]
+    fn test_query_ids_sample() {
+        let ranges = vec![[3, 5], [10, 14], [16, 20], [12, 18]];
+        let ids = vec![17, 1, 5, 8, 11, 32];
+        let matches = query_ids(&ranges, &ids);
+
+        assert_eq!(matches.iter().map(|m| m.id).collect::<Vec<_>>(), ids);
+        assert_eq!(
+            matches[0],
+            IdMatch {
+                id: 17,
+                merged: Some([10, 20]),
+                sources: vec![[12, 18], [16, 20]],
+            }
+        );
+        assert_eq!(matches[1].merged, None);
+        assert!(matches[1].sources.is_empty());
+        assert_eq!(matches[2].sources, vec![[3, 5]]);
+        assert_eq!(matches[4].sources, vec![[10, 14]]);
+        assert_eq!(matches.iter().filter(|m| m.merged.is_some()).count(), 3);
+    }
+
+    #[test]
+    fn test_query_ids_matches_binary_search() {
+        let ranges = vec![
+            [0, 0],
+            [2, 9],
+            [4, 6],
+            [20, 30],
+            [25, 40],
+            [u64::MAX - 1, u64::MAX],
+        ];
+        let merged = merge_intervals(ranges.clone(), RangeKind::Closed, false);
+        let ids: Vec<u64> = (0..50).chain([u64::MAX, u64::MAX - 2]).collect();
+        for m in query_ids(&ranges, &ids) {
+            let expected = binary_search_ranges_kind(&merged, m.id, RangeKind::Closed);
+            assert_eq!(m.merged.is_some(), expected, "id {}", m.id);
+            assert_eq!(m.sources.is_empty(), !expected, "id {}", m.id);
+        }
+    }
+
+    #[test]
+    fn test_matches_csv() {
+        let matches = query_ids(&[[3, 5], [4, 8]], &[4, 9]);
+        let mut out = Vec::new();
+        write_matches_csv(&matches, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "id,fresh,merged_start,merged_end,sources\n4,true,3,8,3-5;4-8\n9,false,,,\n"
+        );
+    }
+
+    #[test]
+    fn test_part1_audit() {
+        let dir = std::env::temp_dir();
+        let path = dir.join("aoc2025_p_05_audit.txt");
+        let csv = dir.join("aoc2025_p_05_audit.csv");
+        std::fs::write(&path, "3-5\n10-14\n16-20\n12-18\n\n1\n5\n8\n11\n17\n32\n").unwrap();
+        let path = path.to_str().unwrap();
+
+        assert_eq!(
+            part1_audit(path, csv.to_str().unwrap()).unwrap(),
+            part1(path)
+        );
+        let csv = std::fs::read_to_string(csv).unwrap();
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(rows.len(), 7);
+        assert_eq!(rows[1], "1,false,,,");
+        assert_eq!(rows[5], "17,true,10,20,12-18;16-20");
+    }
+
+    #[test]
+    fn test_range_index_matches_merge() {
+        let ranges = vec![[3, 5], [10, 14], [16, 20], [12, 18], [1, 2], [21, 21]];
+        let mut index = RangeIndex::new();
+        for r in &ranges {
+            index.insert(*r);
+        }
+        let merged = merge_intervals(ranges.clone(), RangeKind::Closed, false);
+        assert_eq!(index.merged(), merged);
+        assert_eq!(index.covered_len(), total_len(&merged, RangeKind::Closed));
+        for id in [1, 5, 8, 11, 17, 21, 32] {
+            assert_eq!(
+                index.contains(id),
+                binary_search_ranges_kind(&merged, id, RangeKind::Closed)
+            );
+        }
+        assert_eq!(index.stab(17), vec![[12, 18], [16, 20]]);
+        assert_eq!(index.stab(8), Vec::<[u64; 2]>::new());
+    }
+
+    #[test]
+    fn test_range_index_remove() {
+        let mut index = RangeIndex::new();
+        for r in [[10, 14], [16, 20], [12, 18], [12, 18]] {
+            index.insert(r);
+        }
+        assert_eq!(index.merged(), vec![[10, 20]]);
+
+        assert!(index.remove([12, 18]));
+        assert_eq!(index.merged(), vec![[10, 20]]);
+        assert!(index.remove([12, 18]));
+        assert_eq!(index.merged(), vec![[10, 14], [16, 20]]);
+        assert_eq!(index.covered_len(), 10);
+        assert!(!index.contains(15));
+
+        assert!(!index.remove([12, 18]));
+        assert!(index.remove([10, 14]));
+        assert!(index.remove([16, 20]));
+        assert!(index.merged().is_empty());
+        assert_eq!(index.covered_len(), 0);
+    }
+
+    #[test]
+    fn test_range_index_boundaries() {
+        let mut index = RangeIndex::new();
+        index.insert([u64::MAX - 1, u64::MAX]);
+        index.insert([0, u64::MAX - 2]);
+        assert_eq!(index.merged().len(), 2);
+        assert_eq!(index.covered_len(), u64::MAX as u128 + 1);
+        index.insert([u64::MAX - 2, u64::MAX - 1]);
+        assert_eq!(index.merged(), vec![[0, u64::MAX]]);
+        assert_eq!(index.covered_len(), u64::MAX as u128 + 1);
+        assert!(index.remove([u64::MAX - 2, u64::MAX - 1]));
+        assert_eq!(
+            index.merged(),
+            vec![[0, u64::MAX - 2], [u64::MAX - 1, u64::MAX]]
+        );
+    }
+
+    #[test]
+    fn test_range_index_stab_matches_scan() {
+        let mut state = 12345u64;
+        let mut next = |bound: u64| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 33) % bound
+        };
+        let mut index = RangeIndex::new();
+        let mut live: Vec<[u64; 2]> = Vec::new();
+        for step in 0..2000 {
+            if step % 3 == 2 && !live.is_empty() {
+                let r = live.swap_remove(next(live.len() as u64) as usize);
+                assert!(index.remove(r));
+            } else {
+                let start = next(1000);
+                let r = [start, start + next(40)];
+                index.insert(r);
+                live.push(r);
+            }
+            if step % 50 == 0 {
+                live.sort_unstable();
+                for id in (0..1050).step_by(7) {
+                    let expected: Vec<[u64; 2]> = live
+                        .iter()
+                        .filter(|r| r[0] <= id && id <= r[1])
+                        .copied()
+                        .collect();
+                    assert_eq!(index.stab(id), expected, "id {}", id);
+                }
+                assert_eq!(
+                    index.merged(),
+                    merge_intervals(live.clone(), RangeKind::Closed, false)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_incremental_parts() {
+        let path = std::env::temp_dir().join("aoc2025_p_05_incremental.txt");
+        std::fs::write(&path, "3-5\n10-14\n16-20\n12-18\n\n1\n5\n8\n11\n17\n32\n").unwrap();
+        let path = path.to_str().unwrap();
+        assert_eq!(part1_incremental(path), part1(path));
+        assert_eq!(part1_incremental(path), 3);
+        assert_eq!(part_2_incremental(path), part_2(path));
+        assert_eq!(part_2_incremental(path), 14);
+    }
 }
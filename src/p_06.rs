@@ -1,142 +1,479 @@
-use std::{
-    fs::{File, read_to_string},
-    io::{BufRead, BufReader},
-    usize,
-};
+use crate::bigint::BigUint;
+use std::fs::read_to_string;
 
-fn read_input(path: &str) -> (Vec<Vec<u128>>, Vec<char>) {
-    let mut numbers: Vec<Vec<u128>> = Vec::new();
-    let mut operators: Vec<char> = Vec::new();
+/// Horizontal reading direction, for problems and for columns within one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Horizontal {
+    LeftToRight,
+    RightToLeft,
+}
 
-    let file = File::open(path).expect("Unable to read file");
-    let reader = BufReader::new(file);
+/// Vertical reading direction, for digits within a column (and rows in part 1).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Vertical {
+    TopDown,
+    BottomUp,
+}
 
-    let mut lines = reader.lines().peekable();
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperatorRow {
+    Top,
+    Bottom,
+}
 
-    while let Some(line) = lines.next() {
-        let line = line.expect("Unable to read line");
-        if lines.peek().is_none() {
-            operators = _read_operators(&line);
-        } else {
-            numbers.push(_read_num_line(&line));
-        }
+/// How a worksheet is laid out, so one parser handles every orientation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Orientation {
+    pub reading: Horizontal,
+    pub digits: Vertical,
+    pub operator_row: OperatorRow,
+}
+
+impl Orientation {
+    /// The layout from the puzzle: read right-to-left, digits top-down,
+    /// operators on the last line.
+    pub const CEPHALOPOD: Orientation = Orientation {
+        reading: Horizontal::RightToLeft,
+        digits: Vertical::TopDown,
+        operator_row: OperatorRow::Bottom,
+    };
+}
+
+impl Default for Orientation {
+    fn default() -> Self {
+        Self::CEPHALOPOD
     }
+}
 
-    return (numbers, operators);
+/// One problem on the worksheet, sliced out of the raw text between blank
+/// separator columns so every cell keeps its exact character positions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    /// Number rows, each exactly `span.1 - span.0` chars wide.
+    rows: Vec<String>,
+    operator: String,
+    /// Column of the operator's first character in the raw worksheet.
+    operator_col: usize,
+    /// Half-open column range `[start, end)` in the raw worksheet.
+    span: (usize, usize),
 }
 
-fn _read_num_line(line: &str) -> Vec<u128> {
-    line.split_whitespace()
-        .map(|c| c.parse::<u128>().expect("Unable to convert to u128"))
-        .collect()
+impl Problem {
+    /// Numbers read left-to-right along each row.
+    fn row_numbers(&self) -> Vec<BigUint> {
+        self.row_numbers_oriented(Vertical::TopDown)
+    }
+
+    fn row_numbers_oriented(&self, order: Vertical) -> Vec<BigUint> {
+        let rows: Box<dyn Iterator<Item = &String>> = match order {
+            Vertical::TopDown => Box::new(self.rows.iter()),
+            Vertical::BottomUp => Box::new(self.rows.iter().rev()),
+        };
+        rows.map(|r| r.trim())
+            .filter(|r| !r.is_empty())
+            .map(|r| r.parse::<BigUint>().expect("Unable to parse number"))
+            .collect()
+    }
+
+    /// Numbers read top-to-bottom per column, rightmost column first.
+    fn column_numbers(&self) -> Vec<BigUint> {
+        return_significant_sum(&self.rows)
+    }
+
+    fn column_numbers_oriented(&self, orientation: Orientation) -> Vec<BigUint> {
+        read_digit_columns(&self.rows, orientation.reading, orientation.digits)
+    }
 }
 
-fn _read_operators(line: &str) -> Vec<char> {
-    line.split_whitespace()
-        .map(|s| s.chars().next().expect("No operator found"))
-        .collect()
+/// Why a worksheet could not be split into problems.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// Nothing but blank lines, so there is no operator line.
+    MissingOperatorLine,
+    /// The block of columns `span` has no operator under it.
+    MissingOperator { span: (usize, usize) },
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::MissingOperatorLine => write!(f, "worksheet has no operator line"),
+            ParseError::MissingOperator { span: (start, end) } => {
+                write!(f, "no operator under columns {}..{}", start, end)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parses a worksheet with operators on the last line, problems left-to-right.
+fn parse_worksheet(text: &str) -> Result<Vec<Problem>, ParseError> {
+    parse_worksheet_oriented(
+        text,
+        Orientation {
+            reading: Horizontal::LeftToRight,
+            ..Orientation::CEPHALOPOD
+        },
+    )
 }
 
-fn part1(path: &str) -> u128 {
-    let (numbers, operators) = read_input(path);
-    let mut ans = vec![0u128; operators.len()];
-    for i in 0..operators.len() {
-        let op = operators[i];
-        if op == '*' {
-            ans[i] = 1;
+/// Parses a worksheet whose operator line is at `orientation.operator_row`,
+/// returning problems in `orientation.reading` order.
+fn parse_worksheet_oriented(
+    text: &str,
+    orientation: Orientation,
+) -> Result<Vec<Problem>, ParseError> {
+    let is_blank = |l: &Vec<char>| l.iter().all(|c| c.is_whitespace());
+    let mut lines: Vec<Vec<char>> = text
+        .lines()
+        .map(|l| l.chars().collect())
+        .collect::<Vec<_>>();
+    while lines.last().is_some_and(is_blank) {
+        lines.pop();
+    }
+    let leading = lines.iter().take_while(|l| is_blank(l)).count();
+    lines.drain(..leading);
+    let op_line = match orientation.operator_row {
+        OperatorRow::Top if !lines.is_empty() => Some(lines.remove(0)),
+        OperatorRow::Top => None,
+        OperatorRow::Bottom => lines.pop(),
+    }
+    .ok_or(ParseError::MissingOperatorLine)?;
+    let width = lines
+        .iter()
+        .map(|l| l.len())
+        .chain(std::iter::once(op_line.len()))
+        .max()
+        .unwrap_or(0);
+    let char_at = |line: &Vec<char>, col: usize| *line.get(col).unwrap_or(&' ');
+    let is_separator =
+        |col: usize| lines.iter().all(|l| char_at(l, col) == ' ') && char_at(&op_line, col) == ' ';
+
+    let mut problems = Vec::new();
+    let mut col = 0;
+    while col < width {
+        if is_separator(col) {
+            col += 1;
+            continue;
+        }
+        let start = col;
+        while col < width && !is_separator(col) {
+            col += 1;
         }
+        let rows = lines
+            .iter()
+            .map(|l| (start..col).map(|c| char_at(l, c)).collect())
+            .collect();
+        let op_cells: String = (start..col).map(|c| char_at(&op_line, c)).collect();
+        let operator_col = start
+            + op_cells
+                .chars()
+                .position(|c| c != ' ')
+                .ok_or(ParseError::MissingOperator { span: (start, col) })?;
+        problems.push(Problem {
+            rows,
+            operator: op_cells.trim().to_string(),
+            operator_col,
+            span: (start, col),
+        });
+    }
+    if orientation.reading == Horizontal::RightToLeft {
+        problems.reverse();
+    }
+    Ok(problems)
+}
 
-        if op == '+' {
-            for num in numbers.iter() {
-                ans[i] += num[i];
+fn read_worksheet(path: &str) -> Result<Vec<Problem>, ParseError> {
+    parse_worksheet(&read_to_string(path).expect("Unable to read file"))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvalError {
+    UnknownOperator {
+        symbol: String,
+        column: usize,
+    },
+    Overflow {
+        symbol: String,
+        column: usize,
+    },
+    DivisionByZero {
+        symbol: String,
+        column: usize,
+    },
+    /// A problem without numbers whose operator has no identity to fall back on.
+    EmptyProblem {
+        symbol: String,
+        column: usize,
+    },
+    /// The worksheet could not be split into problems.
+    Parse(ParseError),
+}
+
+impl From<ParseError> for EvalError {
+    fn from(error: ParseError) -> Self {
+        EvalError::Parse(error)
+    }
+}
+
+impl std::fmt::Display for EvalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EvalError::UnknownOperator { symbol, column } => {
+                write!(f, "unknown operator `{}` at column {}", symbol, column)
+            }
+            EvalError::Overflow { symbol, column } => {
+                write!(f, "`{}` overflowed at column {}", symbol, column)
+            }
+            EvalError::DivisionByZero { symbol, column } => {
+                write!(f, "`{}` divided by zero at column {}", symbol, column)
             }
-        } else if op == '*' {
-            for num in numbers.iter() {
-                ans[i] *= num[i];
+            EvalError::EmptyProblem { symbol, column } => {
+                write!(f, "`{}` has no numbers at column {}", symbol, column)
             }
+            EvalError::Parse(error) => write!(f, "{}", error),
         }
     }
-    return ans.iter().sum();
 }
 
-fn read_input_2(path: &str) -> (Vec<Vec<String>>, Vec<char>) {
-    let mut operators = Vec::new();
-    let mut rows = Vec::new();
-    let file = File::open(path).expect("Unable to read file");
-    let reader = BufReader::new(file);
-    let mut lines = reader.lines().peekable();
+impl std::error::Error for EvalError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Assoc {
+    Left,
+    Right,
+}
+
+/// A binary operator a worksheet problem can fold its numbers with.
+struct Operator {
+    symbol: &'static str,
+    /// Result for a problem with no numbers, if that makes sense.
+    identity: Option<u128>,
+    assoc: Assoc,
+    /// `None` means overflow (or division by zero for `/` and `%`).
+    apply: fn(u128, u128) -> Option<u128>,
+}
+
+const OPERATORS: &[Operator] = &[
+    Operator {
+        symbol: "+",
+        identity: Some(0),
+        assoc: Assoc::Left,
+        apply: u128::checked_add,
+    },
+    Operator {
+        symbol: "*",
+        identity: Some(1),
+        assoc: Assoc::Left,
+        apply: u128::checked_mul,
+    },
+    Operator {
+        symbol: "-",
+        identity: None,
+        assoc: Assoc::Left,
+        apply: u128::checked_sub,
+    },
+    Operator {
+        symbol: "/",
+        identity: None,
+        assoc: Assoc::Left,
+        apply: u128::checked_div,
+    },
+    Operator {
+        symbol: "%",
+        identity: None,
+        assoc: Assoc::Left,
+        apply: u128::checked_rem,
+    },
+    Operator {
+        symbol: "max",
+        identity: None,
+        assoc: Assoc::Left,
+        apply: |a, b| Some(a.max(b)),
+    },
+    Operator {
+        symbol: "min",
+        identity: None,
+        assoc: Assoc::Left,
+        apply: |a, b| Some(a.min(b)),
+    },
+    Operator {
+        // Right-associative like in ordinary notation: a ^ b ^ c = a ^ (b ^ c).
+        symbol: "^",
+        identity: None,
+        assoc: Assoc::Right,
+        apply: |a, b| a.checked_pow(u32::try_from(b).ok()?),
+    },
+];
 
-    while let Some(line) = lines.next() {
-        let line = line.expect("Unable to read line");
-        if lines.peek().is_none() {
-            operators = _read_operators(&line);
+fn lookup_operator(symbol: &str) -> Option<&'static Operator> {
+    OPERATORS.iter().find(|op| op.symbol == symbol)
+}
+
+/// Folds `nums` with the operator named `symbol`. Numbers are combined in
+/// reading order, grouped according to the operator's associativity.
+fn evaluate(symbol: &str, nums: &[u128], column: usize) -> Result<u128, EvalError> {
+    let op = lookup_operator(symbol).ok_or_else(|| EvalError::UnknownOperator {
+        symbol: symbol.to_string(),
+        column,
+    })?;
+    let fail = |rhs: u128| {
+        let symbol = symbol.to_string();
+        if rhs == 0 && matches!(op.symbol, "/" | "%") {
+            EvalError::DivisionByZero { symbol, column }
         } else {
-            rows.push(_read_num_line_2(&line));
+            EvalError::Overflow { symbol, column }
         }
-    }
-    let height = rows.len();
-    let width = rows[0].len();
+    };
 
-    let mut cols = vec![Vec::with_capacity(height); width];
+    let folded = match op.assoc {
+        Assoc::Left => nums.iter().copied().try_fold(None, |acc, n| match acc {
+            None => Ok(Some(n)),
+            Some(a) => (op.apply)(a, n).map(Some).ok_or_else(|| fail(n)),
+        })?,
+        Assoc::Right => nums
+            .iter()
+            .rev()
+            .copied()
+            .try_fold(None, |acc, n| match acc {
+                None => Ok(Some(n)),
+                Some(b) => (op.apply)(n, b).map(Some).ok_or_else(|| fail(b)),
+            })?,
+    };
+    folded
+        .or(op.identity)
+        .ok_or_else(|| EvalError::EmptyProblem {
+            symbol: symbol.to_string(),
+            column,
+        })
+}
 
-    for row in &rows {
-        for (col_idx, val) in row.iter().enumerate() {
-            cols[col_idx].push(val.clone());
+/// Like `evaluate`, but switches to `BigUint` when the numbers or the checked
+/// `u128` result don't fit. Only `+`, `*`, `max` and `min` have a wide
+/// fallback; the other operators still report overflow.
+fn evaluate_wide(symbol: &str, nums: &[BigUint], column: usize) -> Result<BigUint, EvalError> {
+    let small: Option<Vec<u128>> = nums.iter().map(BigUint::to_u128).collect();
+    if let Some(small) = small {
+        match evaluate(symbol, &small, column) {
+            Err(EvalError::Overflow { .. }) => {}
+            res => return res.map(BigUint::from),
         }
+    } else if lookup_operator(symbol).is_none() {
+        return Err(EvalError::UnknownOperator {
+            symbol: symbol.to_string(),
+            column,
+        });
     }
 
-    cols.reverse();
-    operators.reverse();
+    match symbol {
+        "+" => Ok(nums.iter().cloned().sum()),
+        "*" => Ok(nums.iter().cloned().product()),
+        "max" => Ok(nums.iter().max().cloned().unwrap_or_default()),
+        "min" => Ok(nums.iter().min().cloned().unwrap_or_default()),
+        _ => Err(EvalError::Overflow {
+            symbol: symbol.to_string(),
+            column,
+        }),
+    }
+}
 
-    (cols, operators)
+fn sum_results(
+    results: impl Iterator<Item = Result<BigUint, EvalError>>,
+) -> Result<BigUint, EvalError> {
+    results.sum()
 }
 
-fn _read_num_line_2(line: &str) -> Vec<String> {
-    line.split_whitespace().map(|n| n.to_string()).collect()
+fn try_part1(path: &str) -> Result<BigUint, EvalError> {
+    sum_results(
+        read_worksheet(path)?
+            .iter()
+            .map(|p| evaluate_wide(&p.operator, &p.row_numbers(), p.operator_col)),
+    )
 }
 
-fn part2(path: &str) -> u128 {
-    let (mut cols, mut ops) = read_input_2(path);
+fn part1(path: &str) -> BigUint {
+    try_part1(path).expect("Unable to evaluate worksheet")
+}
 
-    let mut paired: Vec<(Vec<String>, char)> = cols.into_iter().zip(ops.into_iter()).collect();
+fn try_part2(path: &str) -> Result<BigUint, EvalError> {
+    sum_results(
+        read_worksheet(path)?
+            .iter()
+            .map(|p| evaluate_wide(&p.operator, &p.column_numbers(), p.operator_col)),
+    )
+}
 
-    paired.reverse();
+fn part2(path: &str) -> BigUint {
+    try_part2(path).expect("Unable to evaluate worksheet")
+}
 
-    let mut ans = Vec::new();
+fn try_part1_oriented(path: &str, orientation: Orientation) -> Result<BigUint, EvalError> {
+    let text = read_to_string(path).expect("Unable to read file");
+    sum_results(
+        parse_worksheet_oriented(&text, orientation)?
+            .iter()
+            .map(|p| {
+                let nums = p.row_numbers_oriented(orientation.digits);
+                evaluate_wide(&p.operator, &nums, p.operator_col)
+            }),
+    )
+}
 
-    for (nums_col, op) in paired {
-        let nums_to_work = return_significant_sum(&nums_col);
-        let res = if op == '*' {
-            nums_to_work.into_iter().product::<u128>()
-        } else {
-            nums_to_work.into_iter().sum::<u128>()
-        };
-        ans.push(res);
-    }
-    ans.iter().sum()
+fn try_part2_oriented(path: &str, orientation: Orientation) -> Result<BigUint, EvalError> {
+    let text = read_to_string(path).expect("Unable to read file");
+    sum_results(
+        parse_worksheet_oriented(&text, orientation)?
+            .iter()
+            .map(|p| {
+                let nums = p.column_numbers_oriented(orientation);
+                evaluate_wide(&p.operator, &nums, p.operator_col)
+            }),
+    )
+}
+
+/// Reads one number per character position, rightmost position first, taking
+/// digits top-to-bottom. Rows are expected to be column-aligned; blanks are
+/// skipped rather than treated as digits.
+fn return_significant_sum(nums: &[String]) -> Vec<BigUint> {
+    read_digit_columns(nums, Horizontal::RightToLeft, Vertical::TopDown)
 }
 
-fn return_significant_sum(nums: &Vec<String>) -> Vec<u128> {
-    let (max_len, min_len) = _get_digit_bounds(&nums);
-    let mut sol: Vec<u128> = Vec::new();
-    for pos in (1..=max_len).rev() {
+/// General form of `return_significant_sum` for any reading orientation.
+fn read_digit_columns(nums: &[String], reading: Horizontal, digits: Vertical) -> Vec<BigUint> {
+    let (max_len, _) = _get_digit_bounds(nums);
+    let positions: Box<dyn Iterator<Item = usize>> = match reading {
+        Horizontal::LeftToRight => Box::new(1..=max_len),
+        Horizontal::RightToLeft => Box::new((1..=max_len).rev()),
+    };
+    let mut sol: Vec<BigUint> = Vec::new();
+    for pos in positions {
         let mut curr_number = String::new();
-        for num in nums {
-            if num.len() >= pos {
-                curr_number.push(num.chars().nth(pos - 1).expect("Unable to read_char"));
+        let rows: Box<dyn Iterator<Item = &String>> = match digits {
+            Vertical::TopDown => Box::new(nums.iter()),
+            Vertical::BottomUp => Box::new(nums.iter().rev()),
+        };
+        for num in rows {
+            if let Some(ch) = num.chars().nth(pos - 1)
+                && ch != ' '
+            {
+                curr_number.push(ch);
             }
         }
+        if curr_number.is_empty() {
+            continue;
+        }
 
         sol.push(
             curr_number
-                .parse::<u128>()
-                .expect("Unable to convert to u128"),
+                .parse::<BigUint>()
+                .expect("Unable to parse number"),
         );
     }
     sol
 }
 
-fn _get_digit_bounds(nums: &Vec<String>) -> (usize, usize) {
+fn _get_digit_bounds(nums: &[String]) -> (usize, usize) {
     let mut max_len = 0;
     let mut min_len = usize::MAX;
     for n in nums {
@@ -150,8 +487,201 @@ fn _get_digit_bounds(nums: &Vec<String>) -> (usize, usize) {
     (max_len, min_len)
 }
 
+/// What `part2` saw for one problem, kept for debugging unexpected totals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProblemTrace {
+    /// Position of the problem on the worksheet, in reading order.
+    index: usize,
+    problem: Problem,
+    /// Numbers as read by the cephalopod, in evaluation order.
+    numbers: Vec<BigUint>,
+    result: Result<BigUint, EvalError>,
+    /// Sum of all successful results up to and including this problem.
+    running_total: BigUint,
+}
+
+fn trace_problems(problems: Vec<Problem>) -> Vec<ProblemTrace> {
+    let mut total = BigUint::zero();
+    problems
+        .into_iter()
+        .enumerate()
+        .map(|(index, problem)| {
+            let numbers = problem.column_numbers();
+            let result = evaluate_wide(&problem.operator, &numbers, problem.operator_col);
+            if let Ok(r) = &result {
+                total += r;
+            }
+            ProblemTrace {
+                index,
+                problem,
+                numbers,
+                result,
+                running_total: total.clone(),
+            }
+        })
+        .collect()
+}
+
+fn trace_part2(path: &str) -> Result<Vec<ProblemTrace>, ParseError> {
+    Ok(trace_problems(read_worksheet(path)?))
+}
+
+/// Draws each problem as a box: the raw cells, then the numbers read from
+/// them, the operator, and the problem's result.
+fn render_trace(traces: &[ProblemTrace]) -> String {
+    let mut out = String::new();
+    for t in traces {
+        let numbers: Vec<String> = t.numbers.iter().map(|n| n.to_string()).collect();
+        let result = match &t.result {
+            Ok(r) => format!("= {}", r),
+            Err(e) => format!("! {}", e),
+        };
+        let width = t
+            .problem
+            .rows
+            .iter()
+            .map(|r| r.chars().count())
+            .chain(numbers.iter().map(|n| n.len()))
+            .chain([t.problem.operator.len(), result.len()])
+            .max()
+            .unwrap_or(0);
+        let border = format!("+{}+\n", "-".repeat(width + 2));
+
+        out.push_str(&format!(
+            "Problem {} (columns {}..{})\n",
+            t.index + 1,
+            t.problem.span.0,
+            t.problem.span.1
+        ));
+        out.push_str(&border);
+        for row in &t.problem.rows {
+            out.push_str(&format!("| {:<width$} |\n", row));
+        }
+        out.push_str(&border);
+        for n in &numbers {
+            out.push_str(&format!("| {:>width$} |\n", n));
+        }
+        out.push_str(&format!("| {:<width$} |\n", t.problem.operator));
+        out.push_str(&border);
+        out.push_str(&format!("| {:>width$} |\n", result));
+        out.push_str(&border);
+        out.push_str(&format!("running total: {}\n\n", t.running_total));
+    }
+    out
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for ch in s.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Machine-readable form of `render_trace`. Numbers are emitted as strings
+/// since they may not fit in a JSON number.
+fn trace_json(traces: &[ProblemTrace]) -> String {
+    let problems: Vec<String> = traces
+        .iter()
+        .map(|t| {
+            let rows: Vec<String> = t.problem.rows.iter().map(|r| json_string(r)).collect();
+            let numbers: Vec<String> = t
+                .numbers
+                .iter()
+                .map(|n| json_string(&n.to_string()))
+                .collect();
+            let (result, error) = match &t.result {
+                Ok(r) => (json_string(&r.to_string()), "null".to_string()),
+                Err(e) => ("null".to_string(), json_string(&e.to_string())),
+            };
+            format!(
+                "{{\"span\":[{},{}],\"rows\":[{}],\"operator\":{},\"numbers\":[{}],\"result\":{},\"error\":{},\"running_total\":{}}}",
+                t.problem.span.0,
+                t.problem.span.1,
+                rows.join(","),
+                json_string(&t.problem.operator),
+                numbers.join(","),
+                result,
+                error,
+                json_string(&t.running_total.to_string())
+            )
+        })
+        .collect();
+    let total = traces
+        .last()
+        .map(|t| t.running_total.clone())
+        .unwrap_or_default();
+    format!(
+        "{{\"problems\":[{}],\"total\":{}}}",
+        problems.join(","),
+        json_string(&total.to_string())
+    )
+}
+
+/// A problem `part2_traced` could not evaluate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProblemError {
+    /// 1-based, as printed by `render_trace`.
+    pub problem: usize,
+    pub error: EvalError,
+}
+
+impl std::fmt::Display for ProblemError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "problem {}: {}", self.problem, self.error)
+    }
+}
+
+impl std::error::Error for ProblemError {}
+
+/// Writes the boxed trace to `out` and, if `json_path` is given, the JSON
+/// trace there. The trace is written in full even when a problem fails, and
+/// the first failure is then returned instead of the total.
+fn part2_traced<W: std::io::Write>(
+    path: &str,
+    out: &mut W,
+    json_path: Option<&str>,
+) -> Result<BigUint, Box<dyn std::error::Error>> {
+    let traces = trace_part2(path)?;
+    out.write_all(render_trace(&traces).as_bytes())?;
+    if let Some(json_path) = json_path {
+        std::fs::write(json_path, trace_json(&traces))?;
+    }
+    if let Some((t, error)) = traces
+        .iter()
+        .find_map(|t| t.result.as_ref().err().map(|e| (t, e)))
+    {
+        return Err(Box::new(ProblemError {
+            problem: t.index + 1,
+            error: error.clone(),
+        }));
+    }
+    Ok(traces
+        .last()
+        .map(|t| t.running_total.clone())
+        .unwrap_or_default())
+}
+
 mod test {
-    use crate::p_06::{part1, part2, read_input, read_input_2};
+    use crate::bigint::BigUint;
+    use crate::p_06::{
+        EvalError, Horizontal, OperatorRow, Orientation, ParseError, Problem, ProblemError,
+        Vertical, evaluate, evaluate_wide, parse_worksheet, parse_worksheet_oriented, part1, part2,
+        part2_traced, read_worksheet, render_trace, trace_json, trace_problems, try_part1,
+        try_part1_oriented, try_part2_oriented,
+    };
+
+    fn big(nums: &[u128]) -> Vec<BigUint> {
+        nums.iter().map(|n| BigUint::from(*n)).collect()
+    }
+
+    const SAMPLE: &str = "123 328  51 64 \n 45 64  387 23 \n  6 98  215 314\n*   +   *   +  \n";
 
     #[test]
     fn test_sample_1() {
@@ -168,9 +698,8 @@ mod test {
     #[test]
     fn test_sample_2() {
         const PATH: &str = "/Users/saranshagarwal/Code/aoc2025/src/p_06_sample.txt";
-        let (cols, ops) = read_input_2(PATH);
-        for col in cols {
-            println!("{:?}", col);
+        for problem in read_worksheet(PATH).unwrap() {
+            println!("{:?}", problem.column_numbers());
         }
         println!("The answer is : {}", part2(PATH));
     }
@@ -180,4 +709,360 @@ mod test {
         const PATH: &str = "/Users/saranshagarwal/Code/aoc2025/src/p_06.txt";
         println!("The answer is : {}", part2(PATH));
     }
+
+    #[test]
+    fn test_parse_worksheet_sample() {
+        let problems = parse_worksheet(SAMPLE).unwrap();
+        assert_eq!(problems.len(), 4);
+        assert_eq!(
+            problems[3],
+            Problem {
+                rows: vec!["64 ".to_string(), "23 ".to_string(), "314".to_string()],
+                operator: "+".to_string(),
+                operator_col: 12,
+                span: (12, 15),
+            }
+        );
+        assert_eq!(problems[1].span, (4, 7));
+        assert_eq!(problems[1].row_numbers(), big(&[328, 64, 98]));
+        assert_eq!(problems[3].column_numbers(), big(&[4, 431, 623]));
+
+        let path = std::env::temp_dir().join("aoc2025_p_06_sample.txt");
+        std::fs::write(&path, SAMPLE).unwrap();
+        let path = path.to_str().unwrap();
+        assert_eq!(part1(path), BigUint::from(4277556u128));
+        assert_eq!(part2(path), BigUint::from(3263827u128));
+    }
+
+    #[test]
+    fn test_alignment_is_preserved() {
+        // Right- and left-aligned cells produce different cephalopod numbers.
+        let right = parse_worksheet(" 1\n23\n* \n").unwrap();
+        let left = parse_worksheet("1 \n23\n* \n").unwrap();
+        assert_eq!(right[0].column_numbers(), big(&[13, 2]));
+        assert_eq!(left[0].column_numbers(), big(&[3, 12]));
+        assert_eq!(right[0].row_numbers(), left[0].row_numbers());
+    }
+
+    #[test]
+    fn test_operator_registry() {
+        assert_eq!(evaluate("+", &[1, 2, 3], 0), Ok(6));
+        assert_eq!(evaluate("*", &[2, 3, 4], 0), Ok(24));
+        assert_eq!(evaluate("-", &[10, 3, 2], 0), Ok(5));
+        assert_eq!(evaluate("/", &[100, 5, 2], 0), Ok(10));
+        assert_eq!(evaluate("%", &[100, 7], 0), Ok(2));
+        assert_eq!(evaluate("max", &[4, 9, 2], 0), Ok(9));
+        assert_eq!(evaluate("min", &[4, 9, 2], 0), Ok(2));
+        assert_eq!(evaluate("^", &[2, 3, 2], 0), Ok(512));
+        assert_eq!(evaluate("*", &[], 0), Ok(1));
+    }
+
+    #[test]
+    fn test_operator_errors() {
+        assert_eq!(
+            evaluate("?", &[1], 7),
+            Err(EvalError::UnknownOperator {
+                symbol: "?".to_string(),
+                column: 7
+            })
+        );
+        assert_eq!(
+            evaluate("-", &[1, 2], 3),
+            Err(EvalError::Overflow {
+                symbol: "-".to_string(),
+                column: 3
+            })
+        );
+        assert_eq!(
+            evaluate("*", &[u128::MAX, 2], 0),
+            Err(EvalError::Overflow {
+                symbol: "*".to_string(),
+                column: 0
+            })
+        );
+        assert_eq!(
+            evaluate("/", &[1, 0], 2),
+            Err(EvalError::DivisionByZero {
+                symbol: "/".to_string(),
+                column: 2
+            })
+        );
+        assert!(matches!(
+            evaluate("^", &[2, 200], 0),
+            Err(EvalError::Overflow { .. })
+        ));
+        for symbol in ["-", "/", "%", "max", "min", "^"] {
+            assert_eq!(
+                evaluate(symbol, &[], 5),
+                Err(EvalError::EmptyProblem {
+                    symbol: symbol.to_string(),
+                    column: 5
+                })
+            );
+        }
+        assert_eq!(evaluate("+", &[], 5), Ok(0));
+        assert_eq!(
+            evaluate_wide("max", &[], 1),
+            Err(EvalError::EmptyProblem {
+                symbol: "max".to_string(),
+                column: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_worksheet_operators() {
+        let problems = parse_worksheet("10  7   3\n 4  2   9\n-   max ^\n").unwrap();
+        assert_eq!(problems[1].operator, "max");
+        assert_eq!(problems[1].operator_col, 4);
+        let results: Vec<BigUint> = problems
+            .iter()
+            .map(|p| evaluate_wide(&p.operator, &p.row_numbers(), p.operator_col).unwrap())
+            .collect();
+        assert_eq!(results, big(&[6, 7, 19683]));
+
+        let path = std::env::temp_dir().join("aoc2025_p_06_unknown_op.txt");
+        std::fs::write(&path, "1 2\n3 4\n+ ?\n").unwrap();
+        assert_eq!(
+            try_part1(path.to_str().unwrap()),
+            Err(EvalError::UnknownOperator {
+                symbol: "?".to_string(),
+                column: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_wide_fallback() {
+        let max = BigUint::from(u128::MAX);
+        assert_eq!(
+            evaluate_wide("+", &[max.clone(), BigUint::one()], 0)
+                .unwrap()
+                .to_string(),
+            "340282366920938463463374607431768211456"
+        );
+        assert_eq!(
+            evaluate_wide("max", &[max.clone(), &max + &max], 0),
+            Ok(&max + &max)
+        );
+        assert!(matches!(
+            evaluate_wide("-", &[BigUint::zero(), BigUint::one()], 0),
+            Err(EvalError::Overflow { .. })
+        ));
+        assert!(matches!(
+            evaluate_wide("?", &[&max * &max], 4),
+            Err(EvalError::UnknownOperator { column: 4, .. })
+        ));
+    }
+
+    #[test]
+    fn test_huge_worksheet() {
+        // 40 rows of 39 nines: every product and every column number exceeds u128.
+        let row = "9".repeat(39);
+        let mut sheet = String::new();
+        for _ in 0..40 {
+            sheet.push_str(&format!("{} {}\n", row, row));
+        }
+        sheet.push_str(&format!("*{}+\n", " ".repeat(39)));
+
+        let path = std::env::temp_dir().join("aoc2025_p_06_huge.txt");
+        std::fs::write(&path, &sheet).unwrap();
+        let path = path.to_str().unwrap();
+
+        let nines = row.parse::<BigUint>().unwrap();
+        let product: BigUint = std::iter::repeat_n(nines.clone(), 40).product();
+        let sum = &nines * &BigUint::from(40u128);
+        assert_eq!(part1(path), &product + &sum);
+
+        let column = "9".repeat(40).parse::<BigUint>().unwrap();
+        let col_product: BigUint = std::iter::repeat_n(column.clone(), 39).product();
+        let col_sum = &column * &BigUint::from(39u128);
+        assert_eq!(part2(path), &col_product + &col_sum);
+        assert_eq!(part2(path).to_string().len(), 39 * 40);
+    }
+
+    #[test]
+    fn test_render_trace() {
+        let traces = trace_problems(parse_worksheet(SAMPLE).unwrap());
+        assert_eq!(traces[3].numbers, big(&[4, 431, 623]));
+        assert_eq!(traces[3].running_total, BigUint::from(3263827u128));
+
+        let rendered = render_trace(&traces[3..]);
+        let expected = "\
+Problem 4 (columns 12..15)
++--------+
+| 64     |
+| 23     |
+| 314    |
++--------+
+|      4 |
+|    431 |
+|    623 |
+| +      |
++--------+
+| = 1058 |
++--------+
+running total: 3263827
+
+";
+        assert_eq!(rendered, expected);
+    }
+
+    #[test]
+    fn test_trace_json() {
+        let traces = trace_problems(parse_worksheet("12 3\n 4 5\n+  ?\n").unwrap());
+        assert_eq!(
+            trace_json(&traces),
+            concat!(
+                r#"{"problems":["#,
+                r#"{"span":[0,2],"rows":["12"," 4"],"operator":"+","numbers":["24","1"],"#,
+                r#""result":"25","error":null,"running_total":"25"},"#,
+                r#"{"span":[3,4],"rows":["3","5"],"operator":"?","numbers":["35"],"#,
+                r#""result":null,"error":"unknown operator `?` at column 3","running_total":"25"}"#,
+                r#"],"total":"25"}"#
+            )
+        );
+
+        let dir = std::env::temp_dir();
+        let sheet = dir.join("aoc2025_p_06_trace.txt");
+        let json = dir.join("aoc2025_p_06_trace.json");
+        std::fs::write(&sheet, SAMPLE).unwrap();
+        let mut rendered = Vec::new();
+        let total = part2_traced(sheet.to_str().unwrap(), &mut rendered, json.to_str()).unwrap();
+        assert_eq!(total, BigUint::from(3263827u128));
+        let written = std::fs::read_to_string(json).unwrap();
+        assert!(written.ends_with(r#""total":"3263827"}"#));
+        assert_eq!(
+            String::from_utf8(rendered).unwrap(),
+            render_trace(&trace_problems(parse_worksheet(SAMPLE).unwrap()))
+        );
+
+        // A failing problem is still traced, but the total is not reported.
+        std::fs::write(&sheet, "12 3 1\n 4 5 0\n+  ? /\n").unwrap();
+        let mut rendered = Vec::new();
+        let err = part2_traced(sheet.to_str().unwrap(), &mut rendered, None).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ProblemError>(),
+            Some(&ProblemError {
+                problem: 2,
+                error: EvalError::UnknownOperator {
+                    symbol: "?".to_string(),
+                    column: 3
+                }
+            })
+        );
+        assert_eq!(
+            err.to_string(),
+            "problem 2: unknown operator `?` at column 3"
+        );
+        let rendered = String::from_utf8(rendered).unwrap();
+        assert!(rendered.contains("Problem 3 (columns 5..6)"));
+    }
+
+    fn write_sheet(name: &str, text: &str) -> String {
+        let path = std::env::temp_dir().join(name);
+        std::fs::write(&path, text).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_orientations_agree() {
+        let lines: Vec<&str> = SAMPLE.lines().collect();
+        let (ops, nums) = lines.split_last().unwrap();
+
+        // Flipped vertically: operators on top, digits read bottom-up.
+        let mut flipped: Vec<&str> = vec![ops];
+        flipped.extend(nums.iter().rev());
+        let flipped = write_sheet("aoc2025_p_06_flipped.txt", &flipped.join("\n"));
+        let upside_down = Orientation {
+            digits: Vertical::BottomUp,
+            operator_row: OperatorRow::Top,
+            ..Orientation::CEPHALOPOD
+        };
+
+        // Mirrored horizontally: problems and columns read left-to-right.
+        let mirrored: Vec<String> = lines.iter().map(|l| l.chars().rev().collect()).collect();
+        let mirrored = write_sheet("aoc2025_p_06_mirrored.txt", &mirrored.join("\n"));
+        let mirror = Orientation {
+            reading: Horizontal::LeftToRight,
+            ..Orientation::CEPHALOPOD
+        };
+
+        let sample = write_sheet("aoc2025_p_06_oriented.txt", SAMPLE);
+        let expected = BigUint::from(3263827u128);
+        assert_eq!(
+            try_part2_oriented(&sample, Orientation::default()),
+            Ok(expected.clone())
+        );
+        assert_eq!(
+            try_part2_oriented(&flipped, upside_down),
+            Ok(expected.clone())
+        );
+        assert_eq!(try_part2_oriented(&mirrored, mirror), Ok(expected));
+        assert_eq!(
+            try_part1_oriented(&flipped, upside_down),
+            Ok(BigUint::from(4277556u128))
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse_worksheet("\n  \n"),
+            Err(ParseError::MissingOperatorLine)
+        );
+        let top = Orientation {
+            operator_row: OperatorRow::Top,
+            ..Orientation::CEPHALOPOD
+        };
+        assert_eq!(
+            parse_worksheet_oriented("", top),
+            Err(ParseError::MissingOperatorLine)
+        );
+
+        let blank = parse_worksheet("12 34\n 4  5\n+    \n").unwrap_err();
+        assert_eq!(blank, ParseError::MissingOperator { span: (3, 5) });
+        assert_eq!(blank.to_string(), "no operator under columns 3..5");
+        let path = write_sheet("aoc2025_p_06_blank_operator.txt", "12 34\n 4  5\n+    \n");
+        assert_eq!(try_part1(&path), Err(EvalError::Parse(blank)));
+
+        // Operator columns count characters, not bytes.
+        let problems = parse_worksheet("1  23\n×   ×\n").unwrap();
+        assert_eq!(problems[0].operator_col, 0);
+        assert_eq!(problems[1].operator_col, 4);
+        assert_eq!(problems[1].span, (3, 5));
+    }
+
+    #[test]
+    fn test_reading_order() {
+        let top = Orientation {
+            operator_row: OperatorRow::Top,
+            ..Orientation::CEPHALOPOD
+        };
+        let problems = parse_worksheet_oriented("-  +\n12 3\n 4 5\n", top).unwrap();
+        assert_eq!(problems[0].operator, "+");
+        assert_eq!(problems[1].rows, vec!["12".to_string(), " 4".to_string()]);
+
+        let p = &problems[1];
+        let read = |reading, digits| {
+            p.column_numbers_oriented(Orientation {
+                reading,
+                digits,
+                operator_row: OperatorRow::Top,
+            })
+        };
+        assert_eq!(
+            read(Horizontal::RightToLeft, Vertical::TopDown),
+            big(&[24, 1])
+        );
+        assert_eq!(
+            read(Horizontal::LeftToRight, Vertical::TopDown),
+            big(&[1, 24])
+        );
+        assert_eq!(
+            read(Horizontal::LeftToRight, Vertical::BottomUp),
+            big(&[1, 42])
+        );
+        assert_eq!(p.row_numbers_oriented(Vertical::BottomUp), big(&[4, 12]));
+    }
 }
@@ -1,3 +1,4 @@
+use crate::bigint::BigUint;
 use std::{
     collections::{HashMap, HashSet},
     fs::read_to_string,
@@ -15,160 +16,813 @@ fn read_input(path: &str) -> Vec<Vec<char>> {
     grid
 }
 
-fn part_1(path: &str) -> usize {
-    let grid = read_input(path);
-
-    let mut obstacles: HashMap<usize, HashSet<usize>> = HashMap::new();
-    let mut beam_cols: HashSet<usize> = HashSet::new();
-    let mut beam_row = 0;
-
-    // get the starting location
-    for (i, row) in grid.iter().enumerate() {
-        for (j, ch) in row.iter().enumerate() {
-            if *ch == 'S' {
-                beam_row = i;
-                beam_cols.insert(j);
-            } else if *ch == '^' {
-                obstacles
-                    .entry(i)
-                    .and_modify(|vals| {
-                        vals.insert(j);
-                    })
-                    .or_insert_with(|| {
-                        let mut hs = HashSet::new();
-                        hs.insert(j);
-                        hs
-                    });
+/// The tachyon manifold: where the beam enters and where the splitters are.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Manifold {
+    /// `(row, col)` of the `S` tile.
+    start: (usize, usize),
+    splitters: HashMap<usize, HashSet<usize>>,
+    width: usize,
+    height: usize,
+}
+
+impl Manifold {
+    fn from_grid(grid: &[Vec<char>]) -> Self {
+        let mut start = None;
+        let mut splitters: HashMap<usize, HashSet<usize>> = HashMap::new();
+        for (i, row) in grid.iter().enumerate() {
+            for (j, ch) in row.iter().enumerate() {
+                if *ch == 'S' {
+                    start = Some((i, j));
+                } else if *ch == '^' {
+                    splitters.entry(i).or_default().insert(j);
+                }
             }
         }
+        Self {
+            start: start.expect("No start location found"),
+            splitters,
+            width: grid.iter().map(|r| r.len()).max().unwrap_or(0),
+            height: grid.len(),
+        }
     }
 
-    let mut number_splits = 0;
+    pub fn parse(text: &str) -> Self {
+        let grid: Vec<Vec<char>> = text.lines().map(|l| l.chars().collect()).collect();
+        Self::from_grid(&grid)
+    }
 
-    while beam_row < grid.len() - 1 {
-        if !obstacles.contains_key(&(beam_row + 1)) {
-            beam_row += 1;
-            continue;
-        }
-        let next_row_obstacles = obstacles.get(&(beam_row + 1)).unwrap();
-        let mut new_beam_cols: HashSet<usize> = HashSet::new();
-        for col in beam_cols.iter() {
-            if next_row_obstacles.contains(col) {
-                number_splits += 1;
-                if (*col as i32) - 1 >= 0 {
-                    new_beam_cols.insert(col - 1);
-                }
-                if col + 1 < grid[0].len() {
-                    new_beam_cols.insert(col + 1);
-                }
-            } else {
-                new_beam_cols.insert(*col);
+    fn is_splitter(&self, row: usize, col: usize) -> bool {
+        self.splitters
+            .get(&row)
+            .is_some_and(|cols| cols.contains(&col))
+    }
+}
+
+/// Beam storage used while stepping through the manifold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Engine {
+    /// Only occupied columns are stored; best for wide, sparse manifolds.
+    Sparse,
+    /// One counter slot per column; faster when beams fan out across the grid.
+    Dense,
+}
+
+/// A timeline counter did not fit its type while entering `row`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CountOverflow {
+    row: usize,
+}
+
+impl CountOverflow {
+    pub fn at_row(row: usize) -> Self {
+        Self { row }
+    }
+}
+
+impl std::fmt::Display for CountOverflow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "timeline count overflowed at row {}", self.row)
+    }
+}
+
+impl std::error::Error for CountOverflow {}
+
+/// Number type used for beam multiplicities.
+pub trait Counter: Clone + std::fmt::Debug + PartialEq {
+    /// Zero in the same counting domain as `self` (e.g. the same modulus).
+    fn zero_like(&self) -> Self;
+    fn is_zero(&self) -> bool;
+    /// Adds `other` into `self`, returning `false` if the result doesn't fit.
+    fn try_add(&mut self, other: &Self) -> bool;
+}
+
+/// Only tracks whether any timeline reaches a cell; enough for part 1.
+impl Counter for bool {
+    fn zero_like(&self) -> Self {
+        false
+    }
+
+    fn is_zero(&self) -> bool {
+        !*self
+    }
+
+    fn try_add(&mut self, other: &Self) -> bool {
+        *self |= *other;
+        true
+    }
+}
+
+impl Counter for u128 {
+    fn zero_like(&self) -> Self {
+        0
+    }
+
+    fn is_zero(&self) -> bool {
+        *self == 0
+    }
+
+    fn try_add(&mut self, other: &Self) -> bool {
+        match self.checked_add(*other) {
+            Some(v) => {
+                *self = v;
+                true
             }
+            None => false,
+        }
+    }
+}
+
+impl Counter for BigUint {
+    fn zero_like(&self) -> Self {
+        BigUint::zero()
+    }
+
+    fn is_zero(&self) -> bool {
+        BigUint::is_zero(self)
+    }
+
+    fn try_add(&mut self, other: &Self) -> bool {
+        *self += other;
+        true
+    }
+}
+
+/// A count reduced modulo `modulus`, for manifolds too tall to count exactly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModCount {
+    value: u64,
+    modulus: u64,
+}
+
+impl ModCount {
+    fn one(modulus: u64) -> Self {
+        assert!(modulus > 1, "Modulus must be greater than 1");
+        Self { value: 1, modulus }
+    }
+}
+
+impl Counter for ModCount {
+    fn zero_like(&self) -> Self {
+        Self {
+            value: 0,
+            modulus: self.modulus,
         }
-        beam_cols = new_beam_cols;
-        beam_row += 1;
     }
 
-    number_splits
+    fn is_zero(&self) -> bool {
+        self.value == 0
+    }
+
+    fn try_add(&mut self, other: &Self) -> bool {
+        self.value = ((self.value as u128 + other.value as u128) % self.modulus as u128) as u64;
+        true
+    }
 }
 
-fn part_2(path: &str) -> usize {
-    let grid = read_input(path);
+/// What happens to a split branch that would land outside the manifold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Boundary {
+    /// The branch leaves through the side and is reported as an exit.
+    #[default]
+    Drop,
+    /// The branch stays in the edge column.
+    Clamp,
+    /// The branch re-enters from the opposite side.
+    Wrap,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Left,
+    Right,
+}
 
-    let mut obstacles: HashMap<usize, HashSet<usize>> = HashMap::new();
-    let mut beam_cols: HashSet<usize> = HashSet::new();
-    let mut beam_row = 0;
+enum Target {
+    Cell(usize),
+    Exit(Side),
+}
 
-    let mut all_beam_splits: HashMap<usize, usize> = HashMap::new();
+/// Where the two branches of a beam split in column `col` end up.
+fn split_targets(col: usize, width: usize, boundary: Boundary) -> [Target; 2] {
+    let left = if col > 0 {
+        Target::Cell(col - 1)
+    } else {
+        match boundary {
+            Boundary::Drop => Target::Exit(Side::Left),
+            Boundary::Clamp => Target::Cell(0),
+            Boundary::Wrap => Target::Cell(width - 1),
+        }
+    };
+    let right = if col + 1 < width {
+        Target::Cell(col + 1)
+    } else {
+        match boundary {
+            Boundary::Drop => Target::Exit(Side::Right),
+            Boundary::Clamp => Target::Cell(width - 1),
+            Boundary::Wrap => Target::Cell(0),
+        }
+    };
+    [left, right]
+}
 
-    // get the starting location
-    for (i, row) in grid.iter().enumerate() {
-        for (j, ch) in row.iter().enumerate() {
-            if *ch == 'S' {
-                beam_row = i;
-                beam_cols.insert(j);
-                all_beam_splits.insert(j, 1);
-            } else if *ch == '^' {
-                obstacles
-                    .entry(i)
-                    .and_modify(|vals| {
-                        vals.insert(j);
-                    })
-                    .or_insert_with(|| {
-                        let mut hs = HashSet::new();
-                        hs.insert(j);
-                        hs
-                    });
+/// Beams that left the manifold through one side.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SideExit<C = u128> {
+    /// Number of beam positions that exited.
+    beams: usize,
+    /// Timelines carried by those beams.
+    timelines: C,
+}
+
+/// How many beams and timelines left through each side of the manifold.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EdgeReport<C = u128> {
+    left: SideExit<C>,
+    right: SideExit<C>,
+    /// Beams still present on the last row.
+    bottom: SideExit<C>,
+}
+
+#[derive(Debug, Clone)]
+enum Beams<C> {
+    Sparse(HashMap<usize, C>),
+    Dense(Vec<C>),
+}
+
+/// Beams present on one row after propagation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowState<C = u128> {
+    row: usize,
+    /// `(col, multiplicity)` sorted by column; multiplicity is the number of
+    /// timelines in which a beam occupies that cell.
+    beams: Vec<(usize, C)>,
+    /// Distinct beam positions that hit a splitter when entering this row.
+    splits: usize,
+}
+
+impl<C: Counter> RowState<C> {
+    fn timelines(&self, zero: C) -> Result<C, CountOverflow> {
+        let mut total = zero;
+        for (_, n) in &self.beams {
+            if !total.try_add(n) {
+                return Err(CountOverflow { row: self.row });
             }
         }
+        Ok(total)
     }
+}
 
-    let mut number_splits = 0;
+fn deposit<C: Counter>(cell: &mut C, count: &C, row: usize) -> Result<(), CountOverflow> {
+    if cell.try_add(count) {
+        Ok(())
+    } else {
+        Err(CountOverflow { row })
+    }
+}
 
-    while beam_row < grid.len() - 1 {
-        if !obstacles.contains_key(&(beam_row + 1)) {
-            beam_row += 1;
-            continue;
+/// Steps the beam down the manifold one row at a time, starting at the `S` row.
+/// Yields an error and stops if a multiplicity overflows `C`.
+pub struct Propagation<'a, C = u128> {
+    manifold: &'a Manifold,
+    row: usize,
+    beams: Beams<C>,
+    boundary: Boundary,
+    left: SideExit<C>,
+    right: SideExit<C>,
+    started: bool,
+    failed: bool,
+}
+
+impl<'a, C: Counter> Propagation<'a, C> {
+    /// `seed` is the multiplicity of the beam leaving `S`, typically one.
+    fn with_seed(manifold: &'a Manifold, engine: Engine, seed: C) -> Self {
+        let (row, col) = manifold.start;
+        let exit = SideExit {
+            beams: 0,
+            timelines: seed.zero_like(),
+        };
+        let beams = match engine {
+            Engine::Sparse => Beams::Sparse(HashMap::from([(col, seed)])),
+            Engine::Dense => {
+                let mut cells = vec![seed.zero_like(); manifold.width];
+                cells[col] = seed;
+                Beams::Dense(cells)
+            }
+        };
+        Self {
+            manifold,
+            row,
+            beams,
+            boundary: Boundary::default(),
+            left: exit.clone(),
+            right: exit,
+            started: false,
+            failed: false,
+        }
+    }
+
+    fn with_boundary(mut self, boundary: Boundary) -> Self {
+        self.boundary = boundary;
+        self
+    }
+
+    fn snapshot(&self, splits: usize) -> RowState<C> {
+        let mut beams: Vec<(usize, C)> = match &self.beams {
+            Beams::Sparse(cells) => cells.iter().map(|(c, n)| (*c, n.clone())).collect(),
+            Beams::Dense(cells) => cells
+                .iter()
+                .enumerate()
+                .filter(|(_, n)| !n.is_zero())
+                .map(|(c, n)| (c, n.clone()))
+                .collect(),
+        };
+        beams.sort_unstable_by_key(|(c, _)| *c);
+        RowState {
+            row: self.row,
+            beams,
+            splits,
         }
-        let next_row_obstacles = obstacles.get(&(beam_row + 1)).unwrap();
-        let mut new_beam_cols: HashSet<usize> = HashSet::new();
-        for col in beam_cols.iter() {
-            if next_row_obstacles.contains(col) {
-                number_splits += 1;
-                let count_col = all_beam_splits.remove(&col);
-                if (*col as i32) - 1 >= 0 {
-                    new_beam_cols.insert(col - 1);
-                    all_beam_splits
-                        .entry(col - 1)
-                        .and_modify(|v| *v += count_col.unwrap())
-                        .or_insert(count_col.unwrap());
+    }
+
+    /// Moves every beam into the next row, returning how many split.
+    fn step(&mut self) -> Result<usize, CountOverflow> {
+        let next = self.row + 1;
+        let width = self.manifold.width;
+        let boundary = self.boundary;
+        let mut splits = 0;
+        let mut side_exits: Vec<(Side, C)> = Vec::new();
+        match &mut self.beams {
+            Beams::Sparse(cells) => {
+                let Some(row_splitters) = self.manifold.splitters.get(&next) else {
+                    self.row = next;
+                    return Ok(0);
+                };
+                let mut new_cells: HashMap<usize, C> = HashMap::new();
+                for (col, count) in cells.drain() {
+                    let targets = if row_splitters.contains(&col) {
+                        splits += 1;
+                        split_targets(col, width, boundary).into_iter().collect()
+                    } else {
+                        vec![Target::Cell(col)]
+                    };
+                    for target in targets {
+                        match target {
+                            Target::Cell(c) => {
+                                let cell = new_cells.entry(c).or_insert_with(|| count.zero_like());
+                                deposit(cell, &count, next)?;
+                            }
+                            Target::Exit(side) => side_exits.push((side, count.clone())),
+                        }
+                    }
                 }
-                if col + 1 < grid[0].len() {
-                    new_beam_cols.insert(col + 1);
-                    all_beam_splits
-                        .entry(col + 1)
-                        .and_modify(|v| *v += count_col.unwrap())
-                        .or_insert(count_col.unwrap());
+                *cells = new_cells;
+            }
+            Beams::Dense(cells) => {
+                if !self.manifold.splitters.contains_key(&next) {
+                    self.row = next;
+                    return Ok(0);
+                }
+                let mut new_cells: Vec<C> = cells.iter().map(|c| c.zero_like()).collect();
+                for (col, count) in cells.iter().enumerate() {
+                    if count.is_zero() {
+                        continue;
+                    }
+                    if self.manifold.is_splitter(next, col) {
+                        splits += 1;
+                        for target in split_targets(col, width, boundary) {
+                            match target {
+                                Target::Cell(c) => deposit(&mut new_cells[c], count, next)?,
+                                Target::Exit(side) => side_exits.push((side, count.clone())),
+                            }
+                        }
+                    } else {
+                        deposit(&mut new_cells[col], count, next)?;
+                    }
                 }
-            } else {
-                new_beam_cols.insert(*col);
+                *cells = new_cells;
             }
         }
-        beam_cols = new_beam_cols;
-        beam_row += 1;
+        for (side, count) in side_exits {
+            let exit = match side {
+                Side::Left => &mut self.left,
+                Side::Right => &mut self.right,
+            };
+            exit.beams += 1;
+            deposit(&mut exit.timelines, &count, next)?;
+        }
+        self.row = next;
+        Ok(splits)
+    }
+}
+
+impl<C: Counter> Iterator for Propagation<'_, C> {
+    type Item = Result<RowState<C>, CountOverflow>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        if !self.started {
+            self.started = true;
+            return Some(Ok(self.snapshot(0)));
+        }
+        if self.row + 1 >= self.manifold.height {
+            return None;
+        }
+        match self.step() {
+            Ok(splits) => Some(Ok(self.snapshot(splits))),
+            Err(e) => {
+                self.failed = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+fn propagate(manifold: &Manifold, engine: Engine) -> Propagation<'_> {
+    Propagation::with_seed(manifold, engine, 1u128)
+}
+
+/// Timelines passing through every cell: `height` rows of `width` counts.
+pub fn beam_multiplicities(manifold: &Manifold, engine: Engine) -> Vec<Vec<BigUint>> {
+    let mut cells = vec![vec![BigUint::zero(); manifold.width]; manifold.height];
+    for state in Propagation::with_seed(manifold, engine, BigUint::one()) {
+        let state = state.expect("BigUint never overflows");
+        for (col, n) in state.beams {
+            cells[state.row][col] = n;
+        }
+    }
+    cells
+}
+
+pub fn count_splits(manifold: &Manifold, engine: Engine, boundary: Boundary) -> usize {
+    Propagation::with_seed(manifold, engine, true)
+        .with_boundary(boundary)
+        .map(|r| r.expect("Occupancy never overflows").splits)
+        .sum()
+}
+
+/// Runs `rows` to the end and reports where every timeline finished.
+fn finish<C: Counter>(mut rows: Propagation<'_, C>) -> Result<EdgeReport<C>, CountOverflow> {
+    let mut last = None;
+    for row in rows.by_ref() {
+        last = Some(row?);
+    }
+    let last = last.expect("Propagation always yields the start row");
+    let zero = rows.left.timelines.zero_like();
+    Ok(EdgeReport {
+        bottom: SideExit {
+            beams: last.beams.len(),
+            timelines: last.timelines(zero)?,
+        },
+        left: rows.left,
+        right: rows.right,
+    })
+}
+
+/// All timelines, including any that left through the sides.
+fn final_timelines<C: Counter>(rows: Propagation<'_, C>, seed: &C) -> Result<C, CountOverflow> {
+    let row = rows.manifold.height.saturating_sub(1);
+    let report = finish(rows)?;
+    let mut total = seed.zero_like();
+    for side in [report.bottom, report.left, report.right] {
+        if !total.try_add(&side.timelines) {
+            return Err(CountOverflow::at_row(row));
+        }
     }
-    let mut counts = 0;
-    for (_, v) in all_beam_splits.iter() {
-        counts += *v;
+    Ok(total)
+}
+
+/// Where beams end up when branches past the edge follow `boundary`.
+pub fn edge_report(
+    manifold: &Manifold,
+    engine: Engine,
+    boundary: Boundary,
+) -> Result<EdgeReport, CountOverflow> {
+    finish(propagate(manifold, engine).with_boundary(boundary))
+}
+
+/// Exact count in `u128`, or an error naming the row where it overflowed.
+pub fn count_timelines(
+    manifold: &Manifold,
+    engine: Engine,
+    boundary: Boundary,
+) -> Result<u128, CountOverflow> {
+    final_timelines(propagate(manifold, engine).with_boundary(boundary), &1)
+}
+
+fn count_timelines_big(manifold: &Manifold, engine: Engine, boundary: Boundary) -> BigUint {
+    let seed = BigUint::one();
+    final_timelines(
+        Propagation::with_seed(manifold, engine, seed.clone()).with_boundary(boundary),
+        &seed,
+    )
+    .expect("BigUint never overflows")
+}
+
+/// Tries `u128` first and only redoes the walk in `BigUint` if it overflows.
+fn count_timelines_auto(manifold: &Manifold, engine: Engine, boundary: Boundary) -> BigUint {
+    match count_timelines(manifold, engine, boundary) {
+        Ok(n) => BigUint::from(n),
+        Err(_) => count_timelines_big(manifold, engine, boundary),
     }
-    counts
 }
 
+/// Timeline count modulo `modulus` (usually a large prime).
+fn count_timelines_mod(
+    manifold: &Manifold,
+    engine: Engine,
+    boundary: Boundary,
+    modulus: u64,
+) -> u64 {
+    let seed = ModCount::one(modulus);
+    final_timelines(
+        Propagation::with_seed(manifold, engine, seed).with_boundary(boundary),
+        &seed,
+    )
+    .expect("Modular counts never overflow")
+    .value
+}
+
+fn part_1(path: &str, boundary: Boundary) -> usize {
+    let manifold = Manifold::from_grid(&read_input(path));
+    count_splits(&manifold, Engine::Sparse, boundary)
+}
+
+fn part_2(path: &str, boundary: Boundary) -> BigUint {
+    let manifold = Manifold::from_grid(&read_input(path));
+    count_timelines_auto(&manifold, Engine::Dense, boundary)
+}
+
+/// The worked example from the puzzle, for the tests of every module built on p_07.
+#[cfg(test)]
+pub(crate) const SAMPLE: &str = "\
+.......S.......
+...............
+.......^.......
+...............
+......^.^......
+...............
+.....^.^.^.....
+...............
+....^.^...^....
+...............
+...^.^...^.^...
+...............
+..^...^.....^..
+...............
+.^.^.^.^.^...^.
+...............
+";
+
 #[cfg(test)]
 mod test {
-    use crate::p_07::{part_1, part_2, read_input};
+    use crate::bigint::BigUint;
+    use crate::p_07::{
+        Boundary, EdgeReport, Engine, Manifold, SAMPLE, SideExit, count_splits, count_timelines,
+        count_timelines_auto, count_timelines_big, count_timelines_mod, edge_report, part_1,
+        part_2, propagate, read_input,
+    };
 
     #[test]
     fn test_sample_1() {
         const PATH: &str = "/Users/saranshagarwal/Code/aoc2025/src/p_07_sample.txt";
-        println!("The solution is : {}", part_1(PATH));
+        println!("The solution is : {}", part_1(PATH, Boundary::Drop));
     }
 
     #[test]
     fn test_1() {
         const PATH: &str = "/Users/saranshagarwal/Code/aoc2025/src/p_07.txt";
-        println!("The solution is : {}", part_1(PATH));
+        println!("The solution is : {}", part_1(PATH, Boundary::Drop));
     }
 
     #[test]
     fn test_sample_2() {
         const PATH: &str = "/Users/saranshagarwal/Code/aoc2025/src/p_07_sample.txt";
-        println!("The solution is : {}", part_2(PATH));
+        println!("The solution is : {}", part_2(PATH, Boundary::Drop));
     }
 
     #[test]
     fn test_2() {
         const PATH: &str = "/Users/saranshagarwal/Code/aoc2025/src/p_07.txt";
-        println!("The solution is : {}", part_2(PATH));
+        println!("The solution is : {}", part_2(PATH, Boundary::Drop));
+    }
+
+    #[test]
+    fn test_engines_on_sample() {
+        let manifold = Manifold::parse(SAMPLE);
+        for engine in [Engine::Sparse, Engine::Dense] {
+            assert_eq!(count_splits(&manifold, engine, Boundary::Drop), 21);
+            assert_eq!(count_timelines(&manifold, engine, Boundary::Drop), Ok(40));
+        }
+        let sparse: Vec<_> = propagate(&manifold, Engine::Sparse)
+            .map(Result::unwrap)
+            .collect();
+        let dense: Vec<_> = propagate(&manifold, Engine::Dense)
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(sparse, dense);
+        assert_eq!(sparse.len(), 16);
+        assert_eq!(sparse[0].beams, vec![(7, 1)]);
+        assert_eq!(sparse[2].beams, vec![(6, 1), (8, 1)]);
+        assert_eq!(sparse[4].beams, vec![(5, 1), (7, 2), (9, 1)]);
+    }
+
+    /// A full Galton-board pyramid: level `k` has splitters at every other
+    /// column around the start, so after `levels` levels the beam in column
+    /// `start - levels + 2i` has multiplicity C(levels, i) and the total is 2^levels.
+    fn pyramid(levels: usize) -> Manifold {
+        let width = 2 * levels + 3;
+        let center = levels + 1;
+        let mut lines = Vec::new();
+        let mut start = vec!['.'; width];
+        start[center] = 'S';
+        lines.push(start.iter().collect::<String>());
+        for k in 0..levels {
+            lines.push(".".repeat(width));
+            let mut row = vec!['.'; width];
+            for i in 0..=k {
+                row[center - k + 2 * i] = '^';
+            }
+            lines.push(row.iter().collect());
+        }
+        lines.push(".".repeat(width));
+        Manifold::parse(&lines.join("\n"))
+    }
+
+    fn binomial(n: u128, k: u128) -> u128 {
+        (0..k).fold(1, |acc, i| acc * (n - i) / (i + 1))
+    }
+
+    #[test]
+    fn test_pyramid_binomials() {
+        let levels = 12;
+        let manifold = pyramid(levels);
+        let last = propagate(&manifold, Engine::Sparse)
+            .last()
+            .unwrap()
+            .unwrap();
+        let expected: Vec<(usize, u128)> = (0..=levels)
+            .map(|i| (2 * i + 1, binomial(levels as u128, i as u128)))
+            .collect();
+        assert_eq!(last.beams, expected);
+        assert_eq!(
+            count_timelines(&manifold, Engine::Dense, Boundary::Drop),
+            Ok(1 << levels)
+        );
+        assert_eq!(
+            count_splits(&manifold, Engine::Dense, Boundary::Drop),
+            levels * (levels + 1) / 2
+        );
+    }
+
+    #[test]
+    fn test_pyramid_overflow_fallback() {
+        let levels = 130;
+        let manifold = pyramid(levels);
+        assert!(count_timelines(&manifold, Engine::Dense, Boundary::Drop).is_err());
+
+        let expected = (0..levels).fold(BigUint::one(), |acc, _| &acc + &acc);
+        for engine in [Engine::Sparse, Engine::Dense] {
+            assert_eq!(
+                count_timelines_big(&manifold, engine, Boundary::Drop),
+                expected
+            );
+            assert_eq!(
+                count_timelines_auto(&manifold, engine, Boundary::Drop),
+                expected
+            );
+        }
+        assert_eq!(
+            count_timelines_auto(&pyramid(100), Engine::Sparse, Boundary::Drop),
+            BigUint::from(1u128 << 100)
+        );
+    }
+
+    #[test]
+    fn test_pyramid_modular() {
+        const P: u64 = 1_000_000_007;
+        let levels = 300;
+        let manifold = pyramid(levels);
+        let expected = (0..levels).fold(1u64, |acc, _| acc * 2 % P);
+        assert_eq!(
+            count_timelines_mod(&manifold, Engine::Dense, Boundary::Drop, P),
+            expected
+        );
+        assert_eq!(
+            count_timelines_mod(&manifold, Engine::Sparse, Boundary::Drop, P),
+            expected
+        );
+        assert_eq!(
+            count_timelines_mod(&Manifold::parse(SAMPLE), Engine::Dense, Boundary::Drop, 7),
+            40 % 7
+        );
+    }
+
+    fn exit(beams: usize, timelines: u128) -> SideExit {
+        SideExit { beams, timelines }
+    }
+
+    #[test]
+    fn test_edge_splitters_drop() {
+        for engine in [Engine::Sparse, Engine::Dense] {
+            let left = Manifold::parse("S..\n^..\n...\n");
+            assert_eq!(
+                edge_report(&left, engine, Boundary::Drop),
+                Ok(EdgeReport {
+                    left: exit(1, 1),
+                    right: exit(0, 0),
+                    bottom: exit(1, 1),
+                })
+            );
+            assert_eq!(count_timelines(&left, engine, Boundary::Drop), Ok(2));
+
+            let right = Manifold::parse("..S\n..^\n...\n");
+            let report = edge_report(&right, engine, Boundary::Drop).unwrap();
+            assert_eq!(report.right, exit(1, 1));
+            assert_eq!(report.left, exit(0, 0));
+            assert_eq!(count_timelines(&right, engine, Boundary::Drop), Ok(2));
+        }
+    }
+
+    #[test]
+    fn test_edge_splitters_clamp_and_wrap() {
+        let both = Manifold::parse(".S.\n.^.\n^.^\n...\n");
+        for engine in [Engine::Sparse, Engine::Dense] {
+            assert_eq!(
+                edge_report(&both, engine, Boundary::Drop),
+                Ok(EdgeReport {
+                    left: exit(1, 1),
+                    right: exit(1, 1),
+                    bottom: exit(1, 2),
+                })
+            );
+            for boundary in [Boundary::Clamp, Boundary::Wrap] {
+                assert_eq!(
+                    edge_report(&both, engine, boundary),
+                    Ok(EdgeReport {
+                        left: exit(0, 0),
+                        right: exit(0, 0),
+                        bottom: exit(3, 4),
+                    })
+                );
+            }
+            let last = propagate(&both, engine)
+                .with_boundary(Boundary::Wrap)
+                .last()
+                .unwrap()
+                .unwrap();
+            assert_eq!(last.beams, vec![(0, 1), (1, 2), (2, 1)]);
+
+            // Wrapping from column 0 lands on the far side, clamping stays put.
+            let left = Manifold::parse("S..\n^..\n...\n");
+            let wrapped = propagate(&left, engine)
+                .with_boundary(Boundary::Wrap)
+                .last()
+                .unwrap()
+                .unwrap();
+            assert_eq!(wrapped.beams, vec![(1, 1), (2, 1)]);
+            let clamped = propagate(&left, engine)
+                .with_boundary(Boundary::Clamp)
+                .last()
+                .unwrap()
+                .unwrap();
+            assert_eq!(clamped.beams, vec![(0, 1), (1, 1)]);
+        }
+    }
+
+    #[test]
+    fn test_boundary_part_totals() {
+        // Clamping keeps the left branch in column 0, where the second
+        // splitter catches it; wrapping sends it to column 2 instead.
+        let cases = [
+            ("S..\n^..\n^..\n...\n", [(1, 2), (2, 3), (1, 2)]),
+            ("S..\n^..\n..^\n...\n", [(1, 2), (1, 2), (2, 3)]),
+        ];
+        let boundaries = [Boundary::Drop, Boundary::Clamp, Boundary::Wrap];
+        for (idx, (text, expected)) in cases.iter().enumerate() {
+            let path = std::env::temp_dir().join(format!("aoc2025_p_07_boundary_{}.txt", idx));
+            std::fs::write(&path, text).unwrap();
+            let path = path.to_str().unwrap();
+            let manifold = Manifold::parse(text);
+            for (boundary, (splits, timelines)) in boundaries.into_iter().zip(*expected) {
+                assert_eq!(part_1(path, boundary), splits, "{:?}", boundary);
+                assert_eq!(
+                    part_2(path, boundary),
+                    BigUint::from(timelines),
+                    "{:?}",
+                    boundary
+                );
+                for engine in [Engine::Sparse, Engine::Dense] {
+                    assert_eq!(count_timelines(&manifold, engine, boundary), Ok(timelines));
+                    assert_eq!(count_splits(&manifold, engine, boundary), splits);
+                    assert_eq!(
+                        count_timelines_mod(&manifold, engine, boundary, 2),
+                        (timelines % 2) as u64
+                    );
+                }
+            }
+        }
     }
 }
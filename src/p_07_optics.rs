@@ -0,0 +1,547 @@
+// General beam simulator for manifolds with more than the puzzle's `S` and `^`.
+//
+// Tiles:
+//   `.`       empty space
+//   `S`       beam source, emits downward
+//   `^`       splitter with the configured fan-out (2 by default)
+//   `1`..`9`  splitter with that fan-out
+//   `/` `\`   mirrors
+//   `#`       absorber
+//   `A` `v` `<` `>`  merges: every beam entering leaves in the arrow's direction
+
+use crate::bigint::BigUint;
+use crate::p_07::{CountOverflow, Counter};
+use std::collections::HashMap;
+use std::fs::read_to_string;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dir {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Dir {
+    fn delta(self) -> (isize, isize) {
+        match self {
+            Dir::Up => (-1, 0),
+            Dir::Down => (1, 0),
+            Dir::Left => (0, -1),
+            Dir::Right => (0, 1),
+        }
+    }
+
+    /// Unit step perpendicular to travel; splitters spread beams along it.
+    fn lateral(self) -> (isize, isize) {
+        match self {
+            Dir::Up | Dir::Down => (0, 1),
+            Dir::Left | Dir::Right => (1, 0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tile {
+    Empty,
+    Start,
+    /// Branches land beside the splitter in its own row and are acted on by
+    /// the tiles there, so a branch landing on another splitter splits again
+    /// and two adjacent splitters bounce beams between them until `simulate`
+    /// reports a `Cycle`. The p_07 engine instead carries branches down to the
+    /// next row before any tile sees them; the two agree when splitters never
+    /// touch, as in the puzzle input.
+    Splitter {
+        fan_out: usize,
+    },
+    /// `/`
+    MirrorSlash,
+    /// `\`
+    MirrorBackslash,
+    Absorber,
+    Merge(Dir),
+}
+
+impl Tile {
+    /// Direction a beam travelling `dir` leaves a mirror or merge in.
+    fn redirect(self, dir: Dir) -> Dir {
+        match (self, dir) {
+            (Tile::MirrorSlash, Dir::Down) => Dir::Left,
+            (Tile::MirrorSlash, Dir::Up) => Dir::Right,
+            (Tile::MirrorSlash, Dir::Right) => Dir::Up,
+            (Tile::MirrorSlash, Dir::Left) => Dir::Down,
+            (Tile::MirrorBackslash, Dir::Down) => Dir::Right,
+            (Tile::MirrorBackslash, Dir::Up) => Dir::Left,
+            (Tile::MirrorBackslash, Dir::Right) => Dir::Down,
+            (Tile::MirrorBackslash, Dir::Left) => Dir::Up,
+            (Tile::Merge(out), _) => out,
+            _ => dir,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpticsConfig {
+    /// Number of beams a `^` splitter emits.
+    pub splitter_fan_out: usize,
+}
+
+impl Default for OpticsConfig {
+    fn default() -> Self {
+        Self {
+            splitter_fan_out: 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OpticsError {
+    UnknownTile {
+        row: usize,
+        col: usize,
+        ch: char,
+    },
+    NoStart,
+    /// Splitters must emit at least one beam, or their timelines vanish.
+    ZeroFanOut,
+    /// A beam returns to a cell it already passed through in the same
+    /// direction, so the number of timelines is unbounded.
+    Cycle {
+        row: usize,
+        col: usize,
+        dir: Dir,
+    },
+    Overflow(CountOverflow),
+    /// The layout file could not be read.
+    Io {
+        path: String,
+        message: String,
+    },
+}
+
+impl std::fmt::Display for OpticsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OpticsError::UnknownTile { row, col, ch } => {
+                write!(f, "unknown tile `{}` at ({}, {})", ch, row, col)
+            }
+            OpticsError::NoStart => write!(f, "no start tile found"),
+            OpticsError::ZeroFanOut => write!(f, "splitter fan-out must be at least 1"),
+            OpticsError::Cycle { row, col, dir } => {
+                write!(f, "beam cycle through ({}, {}) heading {:?}", row, col, dir)
+            }
+            OpticsError::Overflow(e) => write!(f, "{}", e),
+            OpticsError::Io { path, message } => write!(f, "cannot read {}: {}", path, message),
+        }
+    }
+}
+
+impl std::error::Error for OpticsError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpticsGrid {
+    tiles: Vec<Vec<Tile>>,
+    start: (usize, usize),
+    width: usize,
+    height: usize,
+}
+
+impl OpticsGrid {
+    fn parse(text: &str, config: OpticsConfig) -> Result<Self, OpticsError> {
+        if config.splitter_fan_out == 0 {
+            return Err(OpticsError::ZeroFanOut);
+        }
+        let mut tiles = Vec::new();
+        let mut start = None;
+        for (row, line) in text.lines().enumerate() {
+            let mut tile_row = Vec::new();
+            for (col, ch) in line.chars().enumerate() {
+                let tile = match ch {
+                    '.' => Tile::Empty,
+                    'S' => {
+                        start = Some((row, col));
+                        Tile::Start
+                    }
+                    '^' => Tile::Splitter {
+                        fan_out: config.splitter_fan_out,
+                    },
+                    '1'..='9' => Tile::Splitter {
+                        fan_out: ch.to_digit(10).unwrap() as usize,
+                    },
+                    '/' => Tile::MirrorSlash,
+                    '\\' => Tile::MirrorBackslash,
+                    '#' => Tile::Absorber,
+                    'A' => Tile::Merge(Dir::Up),
+                    'v' => Tile::Merge(Dir::Down),
+                    '<' => Tile::Merge(Dir::Left),
+                    '>' => Tile::Merge(Dir::Right),
+                    _ => return Err(OpticsError::UnknownTile { row, col, ch }),
+                };
+                tile_row.push(tile);
+            }
+            tiles.push(tile_row);
+        }
+        let width = tiles.iter().map(|r| r.len()).max().unwrap_or(0);
+        let height = tiles.len();
+        Ok(Self {
+            tiles,
+            start: start.ok_or(OpticsError::NoStart)?,
+            width,
+            height,
+        })
+    }
+
+    fn tile(&self, row: usize, col: usize) -> Tile {
+        *self.tiles[row].get(col).unwrap_or(&Tile::Empty)
+    }
+
+    fn offset(
+        &self,
+        (row, col): (usize, usize),
+        (dr, dc): (isize, isize),
+        k: isize,
+    ) -> Option<(usize, usize)> {
+        let r = row.checked_add_signed(dr * k)?;
+        let c = col.checked_add_signed(dc * k)?;
+        (r < self.height && c < self.width).then_some((r, c))
+    }
+}
+
+/// A beam sitting on `pos` while travelling `dir`.
+type BeamState = ((usize, usize), Dir);
+
+/// Where a beam goes after the tile under it acts on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Successor {
+    Beam(BeamState),
+    /// Left the grid travelling in this direction.
+    Exit(Dir),
+    Absorbed,
+}
+
+fn successors(grid: &OpticsGrid, (pos, dir): BeamState) -> Vec<Successor> {
+    let step = |pos, dir: Dir| match grid.offset(pos, dir.delta(), 1) {
+        Some(next) => Successor::Beam((next, dir)),
+        None => Successor::Exit(dir),
+    };
+    match grid.tile(pos.0, pos.1) {
+        Tile::Empty | Tile::Start => vec![step(pos, dir)],
+        Tile::Absorber => vec![Successor::Absorbed],
+        tile @ (Tile::MirrorSlash | Tile::MirrorBackslash | Tile::Merge(_)) => {
+            vec![step(pos, tile.redirect(dir))]
+        }
+        Tile::Splitter { fan_out } => {
+            let half = (fan_out / 2) as isize;
+            let mut offsets: Vec<isize> = (1..=half).flat_map(|k| [-k, k]).collect();
+            if fan_out % 2 == 1 {
+                offsets.push(0);
+            }
+            offsets.sort_unstable();
+            offsets
+                .into_iter()
+                .map(|k| {
+                    if k == 0 {
+                        return step(pos, dir);
+                    }
+                    match grid.offset(pos, dir.lateral(), k) {
+                        Some(next) => Successor::Beam((next, dir)),
+                        // Pushed off the side: it leaves sideways.
+                        None => Successor::Exit(if k < 0 {
+                            match dir {
+                                Dir::Up | Dir::Down => Dir::Left,
+                                Dir::Left | Dir::Right => Dir::Up,
+                            }
+                        } else {
+                            match dir {
+                                Dir::Up | Dir::Down => Dir::Right,
+                                Dir::Left | Dir::Right => Dir::Down,
+                            }
+                        }),
+                    }
+                })
+                .collect()
+        }
+    }
+}
+
+/// Outcome of running every timeline through the grid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpticsReport<C> {
+    /// Timelines leaving the grid, by direction of travel.
+    exits: HashMap<Dir, C>,
+    absorbed: C,
+    /// Distinct `(splitter, incoming direction)` pairs that were hit.
+    splits: usize,
+    /// Timelines passing through each cell, summed over directions.
+    cells: HashMap<(usize, usize), C>,
+}
+
+impl<C: Counter> OpticsReport<C> {
+    /// Total timelines that left the grid, or `None` if that overflows `C`.
+    fn exited(&self, zero: &C) -> Option<C> {
+        let mut total = zero.zero_like();
+        for count in self.exits.values() {
+            if !total.try_add(count) {
+                return None;
+            }
+        }
+        Some(total)
+    }
+}
+
+/// Orders the reachable beam states so every state comes after all states
+/// feeding it, failing if they form a cycle.
+fn topo_order(grid: &OpticsGrid, start: BeamState) -> Result<Vec<BeamState>, OpticsError> {
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    enum Mark {
+        InProgress,
+        Done,
+    }
+    let mut marks: HashMap<BeamState, Mark> = HashMap::new();
+    let mut post_order = Vec::new();
+    // Explicit stack of (state, successors still to visit) to avoid recursion.
+    let mut stack: Vec<(BeamState, Vec<BeamState>)> = Vec::new();
+    let children = |s: BeamState| -> Vec<BeamState> {
+        successors(grid, s)
+            .into_iter()
+            .filter_map(|n| match n {
+                Successor::Beam(b) => Some(b),
+                _ => None,
+            })
+            .collect()
+    };
+
+    marks.insert(start, Mark::InProgress);
+    stack.push((start, children(start)));
+    while let Some((state, pending)) = stack.last_mut() {
+        let state = *state;
+        match pending.pop() {
+            Some(child) => match marks.get(&child) {
+                Some(Mark::InProgress) => {
+                    let ((row, col), dir) = child;
+                    return Err(OpticsError::Cycle { row, col, dir });
+                }
+                Some(Mark::Done) => {}
+                None => {
+                    marks.insert(child, Mark::InProgress);
+                    stack.push((child, children(child)));
+                }
+            },
+            None => {
+                marks.insert(state, Mark::Done);
+                post_order.push(state);
+                stack.pop();
+            }
+        }
+    }
+    post_order.reverse();
+    Ok(post_order)
+}
+
+/// Runs a beam of multiplicity `seed` from `S` downward through the grid.
+fn simulate<C: Counter>(grid: &OpticsGrid, seed: C) -> Result<OpticsReport<C>, OpticsError> {
+    let start = (grid.start, Dir::Down);
+    let order = topo_order(grid, start)?;
+    let zero = seed.zero_like();
+    let add = |slot: &mut C, count: &C, row: usize| {
+        if slot.try_add(count) {
+            Ok(())
+        } else {
+            Err(OpticsError::Overflow(CountOverflow::at_row(row)))
+        }
+    };
+
+    let mut counts: HashMap<BeamState, C> = HashMap::from([(start, seed)]);
+    let mut report = OpticsReport {
+        exits: HashMap::new(),
+        absorbed: zero.clone(),
+        splits: 0,
+        cells: HashMap::new(),
+    };
+    for state in order {
+        let Some(count) = counts.remove(&state) else {
+            continue;
+        };
+        let (pos, _) = state;
+        let cell = report.cells.entry(pos).or_insert_with(|| zero.clone());
+        add(cell, &count, pos.0)?;
+        if matches!(grid.tile(pos.0, pos.1), Tile::Splitter { .. }) {
+            report.splits += 1;
+        }
+        for next in successors(grid, state) {
+            let slot = match next {
+                Successor::Beam(b) => counts.entry(b).or_insert_with(|| zero.clone()),
+                Successor::Exit(dir) => report.exits.entry(dir).or_insert_with(|| zero.clone()),
+                Successor::Absorbed => &mut report.absorbed,
+            };
+            add(slot, &count, pos.0)?;
+        }
+    }
+    Ok(report)
+}
+
+/// Simulates the manifold in `path` with exact (unbounded) timeline counts.
+fn simulate_file(path: &str, config: OpticsConfig) -> Result<OpticsReport<BigUint>, OpticsError> {
+    let text = read_to_string(path).map_err(|e| OpticsError::Io {
+        path: path.to_string(),
+        message: e.to_string(),
+    })?;
+    let grid = OpticsGrid::parse(&text, config)?;
+    simulate(&grid, BigUint::one())
+}
+
+/// Human-readable summary of a simulation, one figure per line.
+fn render_report(report: &OpticsReport<BigUint>) -> String {
+    let zero = BigUint::zero();
+    let mut out = String::new();
+    for dir in [Dir::Down, Dir::Left, Dir::Right, Dir::Up] {
+        let count = report.exits.get(&dir).unwrap_or(&zero);
+        out.push_str(&format!("Exited {:?}: {}\n", dir, count));
+    }
+    out.push_str(&format!("Absorbed: {}\n", report.absorbed));
+    out.push_str(&format!("Splits: {}\n", report.splits));
+    let exited = report.exited(&zero).expect("BigUint never overflows");
+    out.push_str(&format!("Timelines: {}\n", &exited + &report.absorbed));
+    out
+}
+
+/// Entry point for `cargo run optics`: simulates a layout and prints where
+/// its timelines end up.
+pub fn run(path: &str, config: OpticsConfig) -> Result<(), OpticsError> {
+    print!("{}", render_report(&simulate_file(path, config)?));
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::bigint::BigUint;
+    use crate::p_07::{Boundary, Engine, Manifold, SAMPLE, count_splits, count_timelines};
+    use crate::p_07_optics::{
+        Dir, OpticsConfig, OpticsError, OpticsGrid, render_report, simulate, simulate_file,
+    };
+
+    #[test]
+    fn test_matches_puzzle_engine() {
+        let grid = OpticsGrid::parse(SAMPLE, OpticsConfig::default()).unwrap();
+        let report = simulate(&grid, 1u128).unwrap();
+        let manifold = Manifold::parse(SAMPLE);
+        assert_eq!(
+            report.splits,
+            count_splits(&manifold, Engine::Sparse, Boundary::Drop)
+        );
+        assert_eq!(
+            report.exited(&0),
+            count_timelines(&manifold, Engine::Sparse, Boundary::Drop).ok()
+        );
+        assert_eq!(report.exits.get(&Dir::Down), Some(&40));
+
+        let path = std::env::temp_dir().join("aoc2025_p_07_optics.txt");
+        std::fs::write(&path, SAMPLE).unwrap();
+        let report = simulate_file(path.to_str().unwrap(), OpticsConfig::default()).unwrap();
+        assert_eq!(report.exited(&BigUint::zero()), Some(BigUint::from(40u128)));
+        assert_eq!(
+            render_report(&report),
+            "Exited Down: 40\nExited Left: 0\nExited Right: 0\nExited Up: 0\n\
+             Absorbed: 0\nSplits: 21\nTimelines: 40\n"
+        );
+
+        let missing = std::env::temp_dir().join("aoc2025_p_07_optics_missing.txt");
+        let _ = std::fs::remove_file(&missing);
+        let err = simulate_file(missing.to_str().unwrap(), OpticsConfig::default()).unwrap_err();
+        assert!(matches!(err, OpticsError::Io { .. }));
+        assert!(err.to_string().starts_with("cannot read "));
+    }
+
+    #[test]
+    fn test_adjacent_splitters() {
+        // The puzzle engine carries both branches down a row; here the right
+        // branch lands on the second splitter, whose left branch comes back.
+        let text = ".S..\n.^^.\n....\n";
+        let manifold = Manifold::parse(text);
+        assert_eq!(
+            count_timelines(&manifold, Engine::Sparse, Boundary::Drop),
+            Ok(2)
+        );
+        let grid = OpticsGrid::parse(text, OpticsConfig::default()).unwrap();
+        assert_eq!(
+            simulate(&grid, 1u128),
+            Err(OpticsError::Cycle {
+                row: 1,
+                col: 1,
+                dir: Dir::Down
+            })
+        );
+    }
+
+    #[test]
+    fn test_mirrors_absorbers_and_merges() {
+        // Down into `\` goes right, `/` sends it up past `S`, and it leaves
+        // through the top edge without reaching the `#`.
+        let grid = OpticsGrid::parse("#..\nS..\n\\./\n", OpticsConfig::default()).unwrap();
+        let report = simulate(&grid, 1u128).unwrap();
+        assert_eq!(report.absorbed, 0);
+        assert_eq!(report.exits.get(&Dir::Up), Some(&1));
+        assert_eq!(report.cells.get(&(1, 2)), Some(&1));
+
+        let grid = OpticsGrid::parse(".S.\n.^.\n>.#\n", OpticsConfig::default()).unwrap();
+        let report = simulate(&grid, 1u128).unwrap();
+        // Left beam is merged rightwards into the absorber, right beam hits it directly.
+        assert_eq!(report.absorbed, 2);
+        assert!(report.exits.is_empty());
+    }
+
+    #[test]
+    fn test_configurable_fan_out() {
+        let text = "..S..\n..^..\n.....\n";
+        for (fan_out, expected) in [(1, 1), (2, 2), (3, 3), (4, 4), (5, 5)] {
+            let config = OpticsConfig {
+                splitter_fan_out: fan_out,
+            };
+            let report = simulate(&OpticsGrid::parse(text, config).unwrap(), 1u128).unwrap();
+            let total = report.exited(&0).unwrap();
+            assert_eq!(total, expected, "fan-out {}", fan_out);
+        }
+        assert_eq!(
+            OpticsGrid::parse(
+                text,
+                OpticsConfig {
+                    splitter_fan_out: 0
+                }
+            ),
+            Err(OpticsError::ZeroFanOut)
+        );
+        let report = simulate(
+            &OpticsGrid::parse("..S..\n..3..\n.....\n", OpticsConfig::default()).unwrap(),
+            1u128,
+        )
+        .unwrap();
+        assert_eq!(report.exits.get(&Dir::Down), Some(&3));
+
+        // Fan-out wider than the grid pushes beams off the sides.
+        let report = simulate(
+            &OpticsGrid::parse(".S.\n.6.\n...\n", OpticsConfig::default()).unwrap(),
+            1u128,
+        )
+        .unwrap();
+        assert_eq!(report.exits.get(&Dir::Left), Some(&2));
+        assert_eq!(report.exits.get(&Dir::Right), Some(&2));
+        assert_eq!(report.exits.get(&Dir::Down), Some(&2));
+    }
+
+    #[test]
+    fn test_cycle_detection() {
+        let grid = OpticsGrid::parse("S..\n>.\\\n...\n\\./\n", OpticsConfig::default()).unwrap();
+        assert!(matches!(
+            simulate(&grid, 1u128),
+            Err(OpticsError::Cycle { .. })
+        ));
+        assert_eq!(
+            OpticsGrid::parse("S?", OpticsConfig::default()),
+            Err(OpticsError::UnknownTile {
+                row: 0,
+                col: 1,
+                ch: '?'
+            })
+        );
+    }
+}
@@ -1,153 +1,615 @@
-use std::cell::RefCell;
-use std::collections::{HashMap, HashSet};
+use crate::bigint::BigUint;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
 use std::fs::read_to_string;
 use std::hash::Hash;
-use std::rc::Rc;
+use std::time::Instant;
 
 fn read_input(path: &str) -> Vec<Pos> {
-    let mut inp: Vec<Pos> = Vec::new();
-    for line in read_to_string(path).expect("Unable to read file").lines() {
-        let mut pos_ = line.splitn(3, ',');
-        inp.push(Pos::new(
-            pos_.next()
-                .expect("No x coordinate found")
-                .parse::<i64>()
-                .expect("Unable to parse x coordinate to i64"),
-            pos_.next()
-                .expect("No y coordinate found")
-                .parse::<i64>()
-                .expect("Unable to parse y coordinate to i64"),
-            pos_.next()
-                .expect("No z coordinate found")
+    parse_positions(&read_to_string(path).expect("Unable to read file"))
+}
+
+pub fn parse_positions(text: &str) -> Vec<Pos> {
+    parse_points(text).expect("Unable to parse junction boxes")
+}
+
+/// Why a line of comma-separated coordinates could not be read. Lines are
+/// numbered from one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    InvalidCoordinate {
+        line: usize,
+        value: String,
+    },
+    Arity {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// More coordinates per line than `parse_any` dispatches to, or none.
+    UnsupportedDimension {
+        found: usize,
+    },
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::InvalidCoordinate { line, value } => {
+                write!(f, "line {}: '{}' is not an integer coordinate", line, value)
+            }
+            ParseError::Arity {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {}: expected {} coordinates, found {}",
+                line, expected, found
+            ),
+            ParseError::UnsupportedDimension { found } => write!(
+                f,
+                "{} coordinates per point are not supported (1 to {} are)",
+                found, MAX_DIMENSION
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+fn parse_coordinates(line_no: usize, line: &str) -> Result<Vec<i64>, ParseError> {
+    line.split(',')
+        .map(|value| {
+            value
+                .trim()
                 .parse::<i64>()
-                .expect("Unable to parse z coordinate to i64"),
-        ));
+                .map_err(|_| ParseError::InvalidCoordinate {
+                    line: line_no,
+                    value: value.to_string(),
+                })
+        })
+        .collect()
+}
+
+fn numbered_lines(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty())
+}
+
+/// Number of coordinates per line, checking that every line agrees with the
+/// first one. Empty input has arity zero.
+pub fn detect_arity(text: &str) -> Result<usize, ParseError> {
+    let mut arity = None;
+    for (line_no, line) in numbered_lines(text) {
+        let found = line.split(',').count();
+        match arity {
+            None => arity = Some(found),
+            Some(expected) if expected != found => {
+                return Err(ParseError::Arity {
+                    line: line_no,
+                    expected,
+                    found,
+                });
+            }
+            Some(_) => {}
+        }
     }
-    inp
+    Ok(arity.unwrap_or(0))
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-pub struct Pos {
-    x: i64,
-    y: i64,
-    z: i64,
+/// One point of dimension `D` per non-empty line.
+pub fn parse_points<const D: usize>(text: &str) -> Result<Vec<Point<D>>, ParseError> {
+    numbered_lines(text)
+        .map(|(line_no, line)| {
+            let coords = parse_coordinates(line_no, line)?;
+            let found = coords.len();
+            let coords = coords.try_into().map_err(|_| ParseError::Arity {
+                line: line_no,
+                expected: D,
+                found,
+            })?;
+            Ok(Point { coords })
+        })
+        .collect()
 }
 
+/// Work to run on points whose dimension is only known once the input has
+/// been read.
+pub trait PointsVisitor {
+    type Output;
+    fn visit<const D: usize>(self, points: Vec<Point<D>>) -> Self::Output;
+}
+
+/// Highest dimension `parse_any` dispatches to.
+pub const MAX_DIMENSION: usize = 8;
+
+/// Parses `text` in the dimension its lines agree on and hands the points
+/// to `visitor`.
+pub fn parse_any<V: PointsVisitor>(text: &str, visitor: V) -> Result<V::Output, ParseError> {
+    Ok(match detect_arity(text)? {
+        1 => visitor.visit(parse_points::<1>(text)?),
+        2 => visitor.visit(parse_points::<2>(text)?),
+        3 => visitor.visit(parse_points::<3>(text)?),
+        4 => visitor.visit(parse_points::<4>(text)?),
+        5 => visitor.visit(parse_points::<5>(text)?),
+        6 => visitor.visit(parse_points::<6>(text)?),
+        7 => visitor.visit(parse_points::<7>(text)?),
+        8 => visitor.visit(parse_points::<8>(text)?),
+        found => return Err(ParseError::UnsupportedDimension { found }),
+    })
+}
+
+/// A junction box in `D` dimensions. Points compare lexicographically by
+/// their coordinates.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub struct Point<const D: usize> {
+    coords: [i64; D],
+}
+
+pub type Pos = Point<3>;
+
 impl Pos {
     pub fn new(x: i64, y: i64, z: i64) -> Self {
-        Self { x: x, y: y, z: z }
+        Self { coords: [x, y, z] }
+    }
+}
+
+impl<const D: usize> Point<D> {
+    pub fn from_coords(coords: [i64; D]) -> Self {
+        Self { coords }
+    }
+
+    /// Squared distance, or `None` if it does not fit in a `u128`. Each
+    /// squared coordinate difference always fits; only their sum can overflow.
+    pub fn checked_dist(&self, p: &Point<D>) -> Option<u128> {
+        (0..D).try_fold(0u128, |acc, axis| {
+            acc.checked_add(self.axis_gap(p, axis).pow(2))
+        })
     }
 
-    pub fn dist(&self, p: &Pos) -> i64 {
-        let dx = self.x as i64 - p.x as i64;
-        let dy = self.y as i64 - p.y as i64;
-        let dz = self.z as i64 - p.z as i64;
+    pub fn dist(&self, p: &Point<D>) -> u128 {
+        self.checked_dist(p)
+            .expect("Squared distance does not fit in a u128")
+    }
 
-        dx * dx + dy * dy + dz * dz
+    pub fn axis(&self, axis: usize) -> i64 {
+        self.coords[axis]
+    }
+
+    /// Signed difference along `axis`, widened so that it cannot overflow.
+    fn axis_diff(&self, p: &Point<D>, axis: usize) -> i128 {
+        self.coords[axis] as i128 - p.coords[axis] as i128
+    }
+
+    fn axis_gap(&self, p: &Point<D>, axis: usize) -> u128 {
+        self.axis_diff(p, axis).unsigned_abs()
+    }
+
+    fn label(&self) -> String {
+        let coords: Vec<String> = self.coords.iter().map(|c| c.to_string()).collect();
+        coords.join(",")
     }
 }
 
-#[derive(Debug)]
-pub struct Edge {
-    u: Pos,
-    v: Pos,
-    dist: i64,
+/// A way of measuring how far apart two junction boxes are.
+///
+/// Distances are unsigned integers so that ties stay exact, and are computed
+/// with checked arithmetic since sums of squares can exceed even `u128`. The
+/// spatial index prunes with [`Metric::axis_bound`], which must never exceed
+/// the distance between any two points that are `diff` apart along `axis`.
+pub trait Metric<const D: usize> {
+    fn checked_dist(&self, a: &Point<D>, b: &Point<D>) -> Option<u128>;
+
+    fn axis_bound(&self, axis: usize, diff: u128) -> u128;
+
+    fn dist(&self, a: &Point<D>, b: &Point<D>) -> u128 {
+        self.checked_dist(a, b)
+            .expect("Distance does not fit in a u128")
+    }
 }
 
-fn build_clusters_1(nodes: &[Pos], num_connections: usize) -> Vec<Vec<Pos>> {
-    let mut remaining: HashSet<Pos> = nodes.iter().copied().collect();
+/// Squared straight-line distance, as used by the puzzle.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SquaredEuclidean;
+
+impl<const D: usize> Metric<D> for SquaredEuclidean {
+    fn checked_dist(&self, a: &Point<D>, b: &Point<D>) -> Option<u128> {
+        a.checked_dist(b)
+    }
 
-    let mut edges: Vec<Edge> = Vec::new();
-    for i in 0..nodes.len() {
-        for j in i + 1..nodes.len() {
-            edges.push(Edge {
-                u: nodes[i],
-                v: nodes[j],
-                dist: nodes[i].dist(&nodes[j]),
-            });
+    fn axis_bound(&self, _axis: usize, diff: u128) -> u128 {
+        diff * diff
+    }
+}
+
+/// Sum of the absolute coordinate differences.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Manhattan;
+
+impl<const D: usize> Metric<D> for Manhattan {
+    fn checked_dist(&self, a: &Point<D>, b: &Point<D>) -> Option<u128> {
+        (0..D).try_fold(0u128, |acc, axis| acc.checked_add(a.axis_gap(b, axis)))
+    }
+
+    fn axis_bound(&self, _axis: usize, diff: u128) -> u128 {
+        diff
+    }
+}
+
+/// Largest absolute coordinate difference.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Chebyshev;
+
+impl<const D: usize> Metric<D> for Chebyshev {
+    fn checked_dist(&self, a: &Point<D>, b: &Point<D>) -> Option<u128> {
+        Some((0..D).map(|axis| a.axis_gap(b, axis)).max().unwrap_or(0))
+    }
+
+    fn axis_bound(&self, _axis: usize, diff: u128) -> u128 {
+        diff
+    }
+}
+
+/// Squared Euclidean distance with a weight per axis.
+#[derive(Debug, Clone, Copy)]
+pub struct WeightedEuclidean<const D: usize> {
+    weights: [u64; D],
+}
+
+impl<const D: usize> WeightedEuclidean<D> {
+    pub fn new(weights: [u64; D]) -> Self {
+        Self { weights }
+    }
+}
+
+impl<const D: usize> Metric<D> for WeightedEuclidean<D> {
+    fn checked_dist(&self, a: &Point<D>, b: &Point<D>) -> Option<u128> {
+        (0..D).try_fold(0u128, |acc, axis| {
+            let term = (self.weights[axis] as u128).checked_mul(a.axis_gap(b, axis).pow(2))?;
+            acc.checked_add(term)
+        })
+    }
+
+    fn axis_bound(&self, axis: usize, diff: u128) -> u128 {
+        self.weights[axis] as u128 * diff * diff
+    }
+}
+
+/// The points are spread too far apart for the metric to measure every pair.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DistanceOverflow {
+    from: String,
+    to: String,
+}
+
+impl std::fmt::Display for DistanceOverflow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "distance between corners ({}) and ({}) of the bounding box does not fit in a u128",
+            self.from, self.to
+        )
+    }
+}
+
+impl std::error::Error for DistanceOverflow {}
+
+/// Checks that every pairwise distance fits in a `u128`. All the metrics
+/// here grow with each coordinate difference, so no pair is further apart
+/// than the two opposite corners of the bounding box. The check is
+/// conservative: it can reject inputs whose actual pairs would all fit.
+pub fn check_extent<const D: usize, M: Metric<D>>(
+    points: &[Point<D>],
+    metric: &M,
+) -> Result<(), DistanceOverflow> {
+    let Some(first) = points.first() else {
+        return Ok(());
+    };
+    let (mut lo, mut hi) = (*first, *first);
+    for p in points {
+        for axis in 0..D {
+            lo.coords[axis] = lo.coords[axis].min(p.coords[axis]);
+            hi.coords[axis] = hi.coords[axis].max(p.coords[axis]);
         }
     }
-    edges.sort_by_key(|e| e.dist);
+    match metric.checked_dist(&lo, &hi) {
+        Some(_) => Ok(()),
+        None => Err(DistanceOverflow {
+            from: lo.label(),
+            to: hi.label(),
+        }),
+    }
+}
 
-    let mut node_to_cluster: HashMap<Pos, Rc<RefCell<Vec<Pos>>>> = HashMap::new();
-    let mut edges_processed = 0;
+/// A connection between two junction boxes with `u <= v`.
+///
+/// Edges are totally ordered by distance, then by `u`, then by `v`,
+/// so that every algorithm in this module breaks ties between equally long
+/// connections the same way regardless of the order the input lists them.
+/// Only coincident points can still tie; those fall back to input order.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub struct Edge<const D: usize> {
+    dist: u128,
+    u: Point<D>,
+    v: Point<D>,
+}
 
-    for edge in edges.iter() {
-        if edges_processed >= num_connections {
-            break;
+impl<const D: usize> Edge<D> {
+    pub fn new(a: Point<D>, b: Point<D>) -> Self {
+        Self {
+            dist: a.dist(&b),
+            u: a.min(b),
+            v: a.max(b),
         }
-        edges_processed += 1;
-        let u_in = remaining.contains(&edge.u);
-        let v_in = remaining.contains(&edge.v);
-        match (u_in, v_in) {
-            (true, true) => {
-                let cluster = Rc::new(RefCell::new(vec![edge.u, edge.v]));
-                node_to_cluster.insert(edge.u, Rc::clone(&cluster));
-                node_to_cluster.insert(edge.v, Rc::clone(&cluster));
-                remaining.remove(&edge.u);
-                remaining.remove(&edge.v);
-            }
-            (true, false) => {
-                if let Some(cluster) = node_to_cluster.get(&edge.v) {
-                    cluster.borrow_mut().push(edge.u);
-                    node_to_cluster.insert(edge.u, Rc::clone(cluster));
-                    remaining.remove(&edge.u);
-                }
-            }
-            (false, true) => {
-                if let Some(cluster) = node_to_cluster.get(&edge.u) {
-                    cluster.borrow_mut().push(edge.v);
-                    node_to_cluster.insert(edge.v, Rc::clone(cluster));
-                    remaining.remove(&edge.v);
-                }
-            }
-            (false, false) => {
-                let cluster_u = node_to_cluster.get(&edge.u).cloned();
-                let cluster_v = node_to_cluster.get(&edge.v).cloned();
-
-                if let (Some(cluster_u), Some(cluster_v)) = (cluster_u, cluster_v) {
-                    if !Rc::ptr_eq(&cluster_u, &cluster_v) {
-                        let nodes_from_v = cluster_v.borrow().clone();
-                        cluster_v.borrow_mut().clear();
-                        for node in nodes_from_v {
-                            cluster_u.borrow_mut().push(node);
-                            node_to_cluster.insert(node, Rc::clone(&cluster_u));
-                        }
-                    }
-                }
+    }
+}
+
+/// Position of each point in `(Point, index)` order, and its inverse.
+fn ranks<const D: usize>(points: &[Point<D>]) -> (Vec<usize>, Vec<usize>) {
+    let mut by_rank: Vec<usize> = (0..points.len()).collect();
+    by_rank.sort_by_key(|&i| (points[i], i));
+    let mut rank = vec![0; points.len()];
+    for (r, &i) in by_rank.iter().enumerate() {
+        rank[i] = r;
+    }
+    (rank, by_rank)
+}
+
+/// Static k-d tree over a slice of points, stored implicitly: the median of
+/// each index range is the node and the two halves are its subtrees. The
+/// splitting axis cycles through all `D` coordinates.
+pub struct KdTree<'a, const D: usize, M: Metric<D> = SquaredEuclidean> {
+    points: &'a [Point<D>],
+    metric: &'a M,
+    order: Vec<usize>,
+    // See `ranks`.
+    rank: Vec<usize>,
+    by_rank: Vec<usize>,
+}
+
+impl<'a, const D: usize> KdTree<'a, D> {
+    pub fn new(points: &'a [Point<D>]) -> Self {
+        Self::with_metric(points, &SquaredEuclidean)
+    }
+}
+
+impl<'a, const D: usize, M: Metric<D>> KdTree<'a, D, M> {
+    pub fn with_metric(points: &'a [Point<D>], metric: &'a M) -> Self {
+        Self::try_with_metric(points, metric).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_with_metric(
+        points: &'a [Point<D>],
+        metric: &'a M,
+    ) -> Result<Self, DistanceOverflow> {
+        const { assert!(D > 0, "Points need at least one coordinate") };
+        check_extent(points, metric)?;
+        let mut order: Vec<usize> = (0..points.len()).collect();
+        Self::build(points, &mut order, 0);
+        let (rank, by_rank) = ranks(points);
+        Ok(Self {
+            points,
+            metric,
+            order,
+            rank,
+            by_rank,
+        })
+    }
+
+    fn build(points: &[Point<D>], order: &mut [usize], depth: usize) {
+        if order.len() <= 1 {
+            return;
+        }
+        let mid = order.len() / 2;
+        let axis = depth % D;
+        order.select_nth_unstable_by_key(mid, |&i| points[i].axis(axis));
+        let (left, right) = order.split_at_mut(mid);
+        Self::build(points, left, depth + 1);
+        Self::build(points, &mut right[1..], depth + 1);
+    }
+
+    /// The `k` points closest to `points[i]`, excluding `i` itself, ordered
+    /// by distance and then by position, as in the [`Edge`] order.
+    pub fn nearest(&self, i: usize, k: usize) -> Vec<(u128, usize)> {
+        self.nearest_ranked(i, k)
+            .into_iter()
+            .map(|(dist, r)| (dist, self.by_rank[r]))
+            .collect()
+    }
+
+    fn nearest_ranked(&self, i: usize, k: usize) -> Vec<(u128, usize)> {
+        let mut best = BinaryHeap::with_capacity(k + 1);
+        if k > 0 {
+            self.search(i, k, 0, self.order.len(), 0, &mut best);
+        }
+        best.into_sorted_vec()
+    }
+
+    fn search(
+        &self,
+        i: usize,
+        k: usize,
+        lo: usize,
+        hi: usize,
+        depth: usize,
+        best: &mut BinaryHeap<(u128, usize)>,
+    ) {
+        if lo >= hi {
+            return;
+        }
+        let mid = lo + (hi - lo) / 2;
+        let node = self.order[mid];
+        let query = &self.points[i];
+        if node != i {
+            best.push((self.metric.dist(query, &self.points[node]), self.rank[node]));
+            if best.len() > k {
+                best.pop();
             }
         }
+
+        let axis = depth % D;
+        let diff = query.axis_diff(&self.points[node], axis);
+        let (near, far) = if diff < 0 {
+            ((lo, mid), (mid + 1, hi))
+        } else {
+            ((mid + 1, hi), (lo, mid))
+        };
+        self.search(i, k, near.0, near.1, depth + 1, best);
+        // Equal distances still have to be visited: a smaller point may win.
+        if best.len() < k
+            || self.metric.axis_bound(axis, diff.unsigned_abs()) <= best.peek().unwrap().0
+        {
+            self.search(i, k, far.0, far.1, depth + 1, best);
+        }
+    }
+}
+
+/// Neighbours of one point in increasing `(distance, rank)` order, fetched
+/// from the tree in batches that double whenever the previous one runs out.
+struct Neighbours {
+    batch: Vec<(u128, usize)>,
+    next: usize,
+    k: usize,
+}
+
+/// Every pair of points as `(distance, i, j)` in increasing [`Edge`] order,
+/// with `points[i] <= points[j]`, without ever materialising the full list of
+/// pairs.
+///
+/// Each point contributes a sorted stream of its neighbours and a heap merges
+/// them; a pair shows up once from each end, and the copies sit next to each
+/// other in the merged order so the second is skipped.
+pub struct EdgeStream<'a, const D: usize, M: Metric<D> = SquaredEuclidean> {
+    tree: KdTree<'a, D, M>,
+    neighbours: Vec<Neighbours>,
+    heap: BinaryHeap<Reverse<HeapEntry<D>>>,
+    last: Option<(u128, usize, usize)>,
+}
+
+/// `(distance, u, v, rank of u, rank of v, owner)`: the [`Edge`] order with
+/// ranks to separate coincident points, and the point whose stream it came from.
+type HeapEntry<const D: usize> = (u128, Point<D>, Point<D>, usize, usize, usize);
+
+const INITIAL_NEIGHBOURS: usize = 8;
+
+impl<'a, const D: usize> EdgeStream<'a, D> {
+    pub fn new(points: &'a [Point<D>]) -> Self {
+        Self::with_metric(points, &SquaredEuclidean)
+    }
+}
+
+impl<'a, const D: usize, M: Metric<D>> EdgeStream<'a, D, M> {
+    pub fn with_metric(points: &'a [Point<D>], metric: &'a M) -> Self {
+        Self::try_with_metric(points, metric).unwrap_or_else(|e| panic!("{}", e))
     }
 
-    for node in remaining.iter() {
-        let cluster = Rc::new(RefCell::new(vec![*node]));
-        node_to_cluster.insert(*node, cluster);
+    pub fn try_with_metric(
+        points: &'a [Point<D>],
+        metric: &'a M,
+    ) -> Result<Self, DistanceOverflow> {
+        let mut stream = Self {
+            tree: KdTree::try_with_metric(points, metric)?,
+            neighbours: (0..points.len())
+                .map(|_| Neighbours {
+                    batch: Vec::new(),
+                    next: 0,
+                    k: 0,
+                })
+                .collect(),
+            heap: BinaryHeap::new(),
+            last: None,
+        };
+        for i in 0..points.len() {
+            stream.advance(i);
+        }
+        Ok(stream)
+    }
+
+    /// Pushes the next neighbour of `owner` onto the heap, if there is one.
+    fn advance(&mut self, owner: usize) {
+        let n = self.tree.points.len();
+        let entry = &mut self.neighbours[owner];
+        if entry.next == entry.batch.len() && entry.batch.len() == entry.k && entry.k < n - 1 {
+            entry.k = (entry.k * 2).max(INITIAL_NEIGHBOURS).min(n - 1);
+            entry.batch = self.tree.nearest_ranked(owner, entry.k);
+        }
+        if let Some(&(dist, other)) = entry.batch.get(entry.next) {
+            entry.next += 1;
+            let own = self.tree.rank[owner];
+            let (a, b) = (own.min(other), own.max(other));
+            let (u, v) = (
+                self.tree.points[self.tree.by_rank[a]],
+                self.tree.points[self.tree.by_rank[b]],
+            );
+            self.heap.push(Reverse((dist, u, v, a, b, owner)));
+        }
+    }
+}
+
+impl<const D: usize, M: Metric<D>> Iterator for EdgeStream<'_, D, M> {
+    type Item = (u128, usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(Reverse((dist, _, _, a, b, owner))) = self.heap.pop() {
+            self.advance(owner);
+            let edge = (dist, a, b);
+            if self.last != Some(edge) {
+                self.last = Some(edge);
+                return Some((dist, self.tree.by_rank[a], self.tree.by_rank[b]));
+            }
+        }
+        None
     }
+}
+
+/// Joins the `num_connections` closest pairs and returns the resulting
+/// clusters, largest first.
+fn build_clusters_1(nodes: &[Pos], num_connections: usize) -> Vec<Vec<Pos>> {
+    build_clusters_with(nodes, num_connections, &SquaredEuclidean)
+}
 
-    let mut unique_clusters = HashSet::new();
-    for cluster_rc in node_to_cluster.values() {
-        unique_clusters.insert(Rc::as_ptr(cluster_rc));
+fn build_clusters_with<const D: usize, M: Metric<D>>(
+    nodes: &[Point<D>],
+    num_connections: usize,
+    metric: &M,
+) -> Vec<Vec<Point<D>>> {
+    let mut uf = UnionFind::new(nodes.len());
+    for (_, i, j) in EdgeStream::with_metric(nodes, metric).take(num_connections) {
+        uf.union(i, j);
     }
-    let clusters: Vec<Vec<Pos>> = unique_clusters
+    uf.components_by_size()
         .into_iter()
-        .map(|ptr| unsafe { (&*ptr).borrow().clone() })
-        .filter(|cluster| !cluster.is_empty())
-        .collect();
-    clusters
+        .map(|members| members.into_iter().map(|i| nodes[i]).collect())
+        .collect()
+}
+
+/// Product of the three largest cluster sizes, for points of any dimension.
+struct Part1 {
+    num_connections: usize,
+}
+
+impl PointsVisitor for Part1 {
+    type Output = usize;
+
+    fn visit<const D: usize>(self, points: Vec<Point<D>>) -> usize {
+        let clusters = build_clusters_with(&points, self.num_connections, &SquaredEuclidean);
+        println!("{:?}", clusters);
+        clusters.iter().take(3).map(|c| c.len()).product()
+    }
 }
 
 fn part_1(path: &str, num_connections: usize) -> usize {
-    let input = read_input(path);
-    let clusters = build_clusters_1(&input, num_connections);
-    println!("{:?}", clusters);
-    let mut cluster_sizes: Vec<usize> = clusters.iter().map(|c| c.len()).collect();
-    cluster_sizes.sort_by(|a, b| b.cmp(a));
-    cluster_sizes.iter().take(3).product()
+    let text = read_to_string(path).expect("Unable to read file");
+    parse_any(&text, Part1 { num_connections }).expect("Unable to parse junction boxes")
 }
 
 pub struct UnionFind {
     parent: Vec<usize>,
     rank: Vec<usize>,
+    size: Vec<usize>,
     num_components: usize,
 }
 
@@ -156,6 +618,7 @@ impl UnionFind {
         Self {
             parent: (0..n).collect(),
             rank: vec![0; n],
+            size: vec![1; n],
             num_components: n,
         }
     }
@@ -177,10 +640,13 @@ impl UnionFind {
 
         if self.rank[root_x] < self.rank[root_y] {
             self.parent[root_x] = root_y;
+            self.size[root_y] += self.size[root_x];
         } else if self.rank[root_x] > self.rank[root_y] {
             self.parent[root_y] = root_x;
+            self.size[root_x] += self.size[root_y];
         } else {
             self.parent[root_y] = root_x;
+            self.size[root_x] += self.size[root_y];
             self.rank[root_x] += 1;
         }
 
@@ -191,25 +657,48 @@ impl UnionFind {
     fn num_components(&self) -> usize {
         self.num_components
     }
-}
 
-fn build_mst(nodes: &[Pos]) -> (Pos, Pos) {
-    let n = nodes.len();
+    /// Number of elements in the component containing `x`.
+    fn component_size(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.size[root]
+    }
 
-    let mut edges: Vec<(i64, usize, usize)> = Vec::new();
-    for i in 0..n {
-        for j in i + 1..n {
-            edges.push((nodes[i].dist(&nodes[j]), i, j));
+    /// Members of every component keyed by root, each list in increasing order.
+    fn members(&mut self) -> HashMap<usize, Vec<usize>> {
+        let mut members: HashMap<usize, Vec<usize>> = HashMap::new();
+        for x in 0..self.parent.len() {
+            let root = self.find(x);
+            members.entry(root).or_default().push(x);
         }
+        members
     }
-    edges.sort_unstable();
+
+    /// All components, largest first; equal sizes are ordered by their
+    /// smallest member.
+    fn components_by_size(&mut self) -> Vec<Vec<usize>> {
+        let mut components: Vec<Vec<usize>> = self.members().into_values().collect();
+        components.sort_by(|a, b| b.len().cmp(&a.len()).then(a[0].cmp(&b[0])));
+        components
+    }
+}
+
+fn build_mst(nodes: &[Pos]) -> (Pos, Pos) {
+    build_mst_with(nodes, &SquaredEuclidean)
+}
+
+fn build_mst_with<const D: usize, M: Metric<D>>(
+    nodes: &[Point<D>],
+    metric: &M,
+) -> (Point<D>, Point<D>) {
+    let n = nodes.len();
 
     let mut uf = UnionFind::new(n);
     let mut last_edge = None;
 
-    for (dist, i, j) in edges.iter() {
-        if uf.union(*i, *j) {
-            last_edge = Some((nodes[*i], nodes[*j]));
+    for (_, i, j) in EdgeStream::with_metric(nodes, metric) {
+        if uf.union(i, j) {
+            last_edge = Some((nodes[i], nodes[j]));
             if uf.num_components() == 1 {
                 break;
             }
@@ -219,95 +708,642 @@ fn build_mst(nodes: &[Pos]) -> (Pos, Pos) {
     last_edge.unwrap()
 }
 
-fn part_2(path: &str) -> i64 {
-    let input = read_input(path);
-    // let (last_u, last_v) = build_clusters_2(&input);
-    // Trying the MST + UnionFind based approach
-    let (last_u, last_v) = build_mst(&input);
-    println!("Last edge: {:?} <-> {:?}", last_u, last_v);
-    last_u.x * last_v.x
-}
+/// Product of the first coordinates of the last MST edge, for points of any
+/// dimension. Two `i64` coordinates always multiply within an `i128`.
+struct Part2;
 
-fn build_clusters_2(nodes: &[Pos]) -> (Pos, Pos) {
-    let mut remaining: HashSet<Pos> = nodes.iter().copied().collect();
+impl PointsVisitor for Part2 {
+    type Output = i128;
 
-    let mut edges: Vec<Edge> = Vec::new();
-    for i in 0..nodes.len() {
-        for j in i + 1..nodes.len() {
-            edges.push(Edge {
-                u: nodes[i],
-                v: nodes[j],
-                dist: nodes[i].dist(&nodes[j]),
-            });
-        }
+    fn visit<const D: usize>(self, points: Vec<Point<D>>) -> i128 {
+        let (last_u, last_v) = build_mst_with(&points, &SquaredEuclidean);
+        println!("Last edge: {:?} <-> {:?}", last_u, last_v);
+        last_u.axis(0) as i128 * last_v.axis(0) as i128
     }
-    edges.sort_by_key(|e| e.dist);
+}
+
+fn part_2(path: &str) -> i128 {
+    let text = read_to_string(path).expect("Unable to read file");
+    parse_any(&text, Part2).expect("Unable to parse junction boxes")
+}
+
+/// How [`spanning_tree`] builds the minimum spanning tree. They all find the
+/// same tree, since the [`Edge`] order leaves no ties to break differently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MstStrategy {
+    /// Edges in increasing order from the spatial index, joined with
+    /// union-find.
+    #[default]
+    Kruskal,
+    /// Dense Prim: O(n²) time and only one candidate edge per point.
+    Prim,
+    /// Rounds of cheapest outgoing edge per component, each round a full
+    /// O(n²) scan without storing any edges.
+    Boruvka,
+}
 
-    let mut node_to_cluster: HashMap<Pos, Rc<RefCell<Vec<Pos>>>> = HashMap::new();
-    let mut num_clusters = nodes.len();
-    let mut last_edge: Option<(Pos, Pos)> = None;
+pub const MST_STRATEGIES: [MstStrategy; 3] = [
+    MstStrategy::Kruskal,
+    MstStrategy::Prim,
+    MstStrategy::Boruvka,
+];
 
-    for edge in edges.iter() {
-        if num_clusters == 1 {
+/// Minimum spanning tree as `(distance, i, j)` edges with
+/// `nodes[i] <= nodes[j]`, in [`Edge`] order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpanningTree {
+    edges: Vec<(u128, usize, usize)>,
+    total: BigUint,
+}
+
+/// Sort key of the edge between `i` and `j`, matching the [`Edge`] order with
+/// ranks to separate coincident points.
+type EdgeKey<const D: usize> = (u128, Point<D>, Point<D>, usize, usize);
+
+fn edge_key<const D: usize>(
+    points: &[Point<D>],
+    rank: &[usize],
+    dist: u128,
+    i: usize,
+    j: usize,
+) -> EdgeKey<D> {
+    let (a, b) = if rank[i] < rank[j] { (i, j) } else { (j, i) };
+    (dist, points[a], points[b], rank[a], rank[b])
+}
+
+pub fn spanning_tree<const D: usize, M: Metric<D>>(
+    nodes: &[Point<D>],
+    metric: &M,
+    strategy: MstStrategy,
+) -> Result<SpanningTree, DistanceOverflow> {
+    check_extent(nodes, metric)?;
+    let (rank, _) = ranks(nodes);
+    let mut edges = match strategy {
+        MstStrategy::Kruskal => kruskal(nodes, metric),
+        MstStrategy::Prim => prim(nodes, metric, &rank),
+        MstStrategy::Boruvka => boruvka(nodes, metric, &rank),
+    };
+    edges = edges
+        .into_iter()
+        .map(|(dist, i, j)| {
+            if rank[i] < rank[j] {
+                (dist, i, j)
+            } else {
+                (dist, j, i)
+            }
+        })
+        .collect();
+    edges.sort_by_key(|&(dist, i, j)| edge_key(nodes, &rank, dist, i, j));
+    let total = edges.iter().map(|&(dist, _, _)| BigUint::from(dist)).sum();
+    Ok(SpanningTree { edges, total })
+}
+
+fn kruskal<const D: usize, M: Metric<D>>(
+    nodes: &[Point<D>],
+    metric: &M,
+) -> Vec<(u128, usize, usize)> {
+    let mut uf = UnionFind::new(nodes.len());
+    let mut edges = Vec::with_capacity(nodes.len().saturating_sub(1));
+    for (dist, i, j) in EdgeStream::with_metric(nodes, metric) {
+        if uf.num_components() <= 1 {
             break;
         }
+        if uf.union(i, j) {
+            edges.push((dist, i, j));
+        }
+    }
+    edges
+}
 
-        let u_in = remaining.contains(&edge.u);
-        let v_in = remaining.contains(&edge.v);
-        match (u_in, v_in) {
-            (true, true) => {
-                let cluster = Rc::new(RefCell::new(vec![edge.u, edge.v]));
-                node_to_cluster.insert(edge.u, Rc::clone(&cluster));
-                node_to_cluster.insert(edge.v, Rc::clone(&cluster));
-                remaining.remove(&edge.u);
-                remaining.remove(&edge.v);
-                num_clusters -= 1;
-                last_edge = Some((edge.u, edge.v));
+fn prim<const D: usize, M: Metric<D>>(
+    nodes: &[Point<D>],
+    metric: &M,
+    rank: &[usize],
+) -> Vec<(u128, usize, usize)> {
+    let n = nodes.len();
+    let mut edges = Vec::with_capacity(n.saturating_sub(1));
+    if n == 0 {
+        return edges;
+    }
+    let mut in_tree = vec![false; n];
+    // Cheapest known edge from each outside point into the tree.
+    let mut best: Vec<Option<(EdgeKey<D>, usize)>> = vec![None; n];
+    let mut current = 0;
+    for _ in 1..n {
+        in_tree[current] = true;
+        let mut next: Option<usize> = None;
+        for w in 0..n {
+            if in_tree[w] {
+                continue;
             }
-            (true, false) => {
-                if let Some(cluster) = node_to_cluster.get(&edge.v) {
-                    cluster.borrow_mut().push(edge.u);
-                    node_to_cluster.insert(edge.u, Rc::clone(cluster));
-                    remaining.remove(&edge.u);
-                    num_clusters -= 1;
-                    last_edge = Some((edge.u, edge.v));
-                }
+            let key = edge_key(
+                nodes,
+                rank,
+                metric.dist(&nodes[current], &nodes[w]),
+                current,
+                w,
+            );
+            if best[w].as_ref().is_none_or(|(k, _)| key < *k) {
+                best[w] = Some((key, current));
             }
-            (false, true) => {
-                if let Some(cluster) = node_to_cluster.get(&edge.u) {
-                    cluster.borrow_mut().push(edge.v);
-                    node_to_cluster.insert(edge.v, Rc::clone(cluster));
-                    remaining.remove(&edge.v);
-                    num_clusters -= 1;
-                    last_edge = Some((edge.u, edge.v));
-                }
+            if next.is_none_or(|v| best[w].as_ref().unwrap().0 < best[v].as_ref().unwrap().0) {
+                next = Some(w);
             }
-            (false, false) => {
-                let cluster_u = node_to_cluster.get(&edge.u).cloned();
-                let cluster_v = node_to_cluster.get(&edge.v).cloned();
-
-                if let (Some(cluster_u), Some(cluster_v)) = (cluster_u, cluster_v) {
-                    if !Rc::ptr_eq(&cluster_u, &cluster_v) {
-                        let nodes_from_v = cluster_v.borrow().clone();
-                        cluster_v.borrow_mut().clear();
-                        for node in nodes_from_v {
-                            cluster_u.borrow_mut().push(node);
-                            node_to_cluster.insert(node, Rc::clone(&cluster_u));
-                        }
-                        num_clusters -= 1;
-                        last_edge = Some((edge.u, edge.v));
+        }
+        let v = next.unwrap();
+        let (key, parent) = best[v].take().unwrap();
+        edges.push((key.0, parent, v));
+        current = v;
+    }
+    edges
+}
+
+fn boruvka<const D: usize, M: Metric<D>>(
+    nodes: &[Point<D>],
+    metric: &M,
+    rank: &[usize],
+) -> Vec<(u128, usize, usize)> {
+    let n = nodes.len();
+    let mut uf = UnionFind::new(n);
+    let mut edges = Vec::with_capacity(n.saturating_sub(1));
+    while uf.num_components() > 1 {
+        let roots: Vec<usize> = (0..n).map(|i| uf.find(i)).collect();
+        // Cheapest edge leaving each component, indexed by its root.
+        let mut cheapest: Vec<Option<(EdgeKey<D>, usize, usize)>> = vec![None; n];
+        for i in 0..n {
+            for j in i + 1..n {
+                if roots[i] == roots[j] {
+                    continue;
+                }
+                let key = edge_key(nodes, rank, metric.dist(&nodes[i], &nodes[j]), i, j);
+                for root in [roots[i], roots[j]] {
+                    if cheapest[root].as_ref().is_none_or(|(k, _, _)| key < *k) {
+                        cheapest[root] = Some((key, i, j));
                     }
                 }
             }
         }
+        for (key, i, j) in cheapest.into_iter().flatten() {
+            // Two components often pick the same edge; only the first joins.
+            if uf.union(i, j) {
+                edges.push((key.0, i, j));
+            }
+        }
     }
+    edges
+}
 
-    last_edge.unwrap()
+impl SpanningTree {
+    pub fn edges(&self) -> &[(u128, usize, usize)] {
+        &self.edges
+    }
+
+    pub fn total(&self) -> &BigUint {
+        &self.total
+    }
+
+    /// Graphviz DOT, with each node labelled by its coordinates and each edge
+    /// by its distance.
+    pub fn to_dot<const D: usize>(&self, nodes: &[Point<D>]) -> String {
+        let mut out = String::from("graph mst {\n");
+        for (i, p) in nodes.iter().enumerate() {
+            out.push_str(&format!("  {} [label=\"{}\"];\n", i, p.label()));
+        }
+        for (dist, i, j) in &self.edges {
+            out.push_str(&format!("  {} -- {} [label=\"{}\"];\n", i, j, dist));
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// GraphML. Distances are stored as strings since they may not fit the
+    /// format's `long`.
+    pub fn to_graphml<const D: usize>(&self, nodes: &[Point<D>]) -> String {
+        let mut out = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n  \
+             <key id=\"pos\" for=\"node\" attr.name=\"position\" attr.type=\"string\"/>\n  \
+             <key id=\"dist\" for=\"edge\" attr.name=\"distance\" attr.type=\"string\"/>\n  \
+             <graph id=\"mst\" edgedefault=\"undirected\">\n",
+        );
+        for (i, p) in nodes.iter().enumerate() {
+            out.push_str(&format!(
+                "    <node id=\"n{}\"><data key=\"pos\">{}</data></node>\n",
+                i,
+                p.label()
+            ));
+        }
+        for (dist, i, j) in &self.edges {
+            out.push_str(&format!(
+                "    <edge source=\"n{}\" target=\"n{}\"><data key=\"dist\">{}</data></edge>\n",
+                i, j, dist
+            ));
+        }
+        out.push_str("  </graph>\n</graphml>\n");
+        out
+    }
+
+    /// One row per edge: point indices, quoted coordinates and distance.
+    pub fn to_csv<const D: usize>(&self, nodes: &[Point<D>]) -> String {
+        let mut out = String::from("u,v,u_pos,v_pos,distance\n");
+        for &(dist, i, j) in &self.edges {
+            out.push_str(&format!(
+                "{},{},\"{}\",\"{}\",{}\n",
+                i,
+                j,
+                nodes[i].label(),
+                nodes[j].label(),
+                dist
+            ));
+        }
+        out
+    }
+}
+
+/// Deterministic pseudo-random points in `[0, range)^D`, the workload for
+/// `benchmark_mst` and the tests. Not meant as input generation otherwise.
+fn random_points<const D: usize>(n: usize, range: i64, mut seed: u64) -> Vec<Point<D>> {
+    let mut next = || {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((seed >> 33) % range as u64) as i64
+    };
+    (0..n)
+        .map(|_| Point::from_coords(std::array::from_fn(|_| next())))
+        .collect()
+}
+
+/// Times every strategy on `n` random points and checks they agree, failing
+/// on the first strategy whose tree differs from the first one's.
+pub fn benchmark_mst(n: usize) -> Result<(), Box<dyn std::error::Error>> {
+    let nodes: Vec<Pos> = random_points(n, 100_000, 42);
+    let mut reference: Option<SpanningTree> = None;
+    println!("{:<10} {:>12} {:>24}", "strategy", "time", "total weight");
+    for strategy in MST_STRATEGIES {
+        let start = Instant::now();
+        let tree = spanning_tree(&nodes, &SquaredEuclidean, strategy)?;
+        let elapsed = start.elapsed();
+        println!(
+            "{:<10} {:>12.3?} {:>24}",
+            format!("{:?}", strategy),
+            elapsed,
+            tree.total
+        );
+        match &reference {
+            Some(expected) if &tree != expected => {
+                return Err(format!(
+                    "{:?} disagrees with {:?}: total weight {} vs {}",
+                    strategy, MST_STRATEGIES[0], tree.total, expected.total
+                )
+                .into());
+            }
+            Some(_) => {}
+            None => reference = Some(tree),
+        }
+    }
+    Ok(())
+}
+
+/// One step of single-linkage clustering: clusters `left` and `right` were
+/// joined by the edge between points `u` and `v`.
+///
+/// Clusters are numbered like leaves first: `0..n` are the points themselves
+/// and merge `k` creates cluster `n + k`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Merge {
+    left: usize,
+    right: usize,
+    dist: u128,
+    size: usize,
+    u: usize,
+    v: usize,
+}
+
+/// Every merge of single-linkage clustering in the order they happen, which
+/// is the order Kruskal's algorithm adds edges to the minimum spanning tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dendrogram {
+    leaves: usize,
+    merges: Vec<Merge>,
+}
+
+impl Dendrogram {
+    pub fn new<const D: usize>(nodes: &[Point<D>]) -> Self {
+        Self::with_metric(nodes, &SquaredEuclidean)
+    }
+
+    pub fn with_metric<const D: usize, M: Metric<D>>(nodes: &[Point<D>], metric: &M) -> Self {
+        let n = nodes.len();
+        let mut uf = UnionFind::new(n);
+        // Cluster id currently represented by each union-find root.
+        let mut cluster: Vec<usize> = (0..n).collect();
+        let mut merges = Vec::with_capacity(n.saturating_sub(1));
+
+        for (dist, u, v) in EdgeStream::with_metric(nodes, metric) {
+            if uf.num_components() <= 1 {
+                break;
+            }
+            let (left, right) = (cluster[uf.find(u)], cluster[uf.find(v)]);
+            if uf.union(u, v) {
+                let root = uf.find(u);
+                cluster[root] = n + merges.len();
+                merges.push(Merge {
+                    left,
+                    right,
+                    dist,
+                    size: uf.component_size(root),
+                    u,
+                    v,
+                });
+            }
+        }
+        Self { leaves: n, merges }
+    }
+
+    /// Replays the first `count` merges.
+    fn replay(&self, count: usize) -> Vec<Vec<usize>> {
+        let mut uf = UnionFind::new(self.leaves);
+        for merge in &self.merges[..count] {
+            uf.union(merge.u, merge.v);
+        }
+        uf.components_by_size()
+    }
+
+    /// Clusters formed by every merge no longer than `threshold`, largest
+    /// first.
+    pub fn cut_at_distance(&self, threshold: u128) -> Vec<Vec<usize>> {
+        self.replay(self.merges.partition_point(|m| m.dist <= threshold))
+    }
+
+    /// Exactly `clusters` clusters (clamped to `1..=n`), largest first.
+    pub fn cut_into(&self, clusters: usize) -> Vec<Vec<usize>> {
+        let clusters = clusters.clamp(1.min(self.leaves), self.leaves);
+        self.replay(self.leaves - clusters)
+    }
+
+    fn height(&self, id: usize) -> u128 {
+        if id < self.leaves {
+            0
+        } else {
+            self.merges[id - self.leaves].dist
+        }
+    }
+
+    /// Newick tree with leaves labelled by their quoted coordinates. Branch
+    /// lengths are differences between merge heights, in the units of the
+    /// metric.
+    pub fn to_newick<const D: usize>(&self, nodes: &[Point<D>]) -> String {
+        // Built bottom-up rather than recursively: a chain of n merges is n deep.
+        let mut subtrees: Vec<String> = nodes.iter().map(|p| format!("'{}'", p.label())).collect();
+        for merge in &self.merges {
+            let height = merge.dist;
+            let left = std::mem::take(&mut subtrees[merge.left]);
+            let right = std::mem::take(&mut subtrees[merge.right]);
+            subtrees.push(format!(
+                "({}:{},{}:{})",
+                left,
+                height - self.height(merge.left),
+                right,
+                height - self.height(merge.right)
+            ));
+        }
+        match subtrees.pop() {
+            Some(root) if self.merges.len() + 1 == self.leaves => format!("{};", root),
+            _ => ";".to_string(),
+        }
+    }
+
+    /// Distances are written as strings: squared distances easily pass 2^53,
+    /// beyond which readers that parse numbers as `f64` lose precision.
+    pub fn to_json<const D: usize>(&self, nodes: &[Point<D>]) -> String {
+        let leaves: Vec<String> = nodes.iter().map(|p| format!("[{}]", p.label())).collect();
+        let merges: Vec<String> = self
+            .merges
+            .iter()
+            .map(|m| {
+                format!(
+                    "{{\"left\":{},\"right\":{},\"distance\":\"{}\",\"size\":{},\"edge\":[{},{}]}}",
+                    m.left, m.right, m.dist, m.size, m.u, m.v
+                )
+            })
+            .collect();
+        format!(
+            "{{\"leaves\":[{}],\"merges\":[{}]}}",
+            leaves.join(","),
+            merges.join(",")
+        )
+    }
 }
 
+/// The worked example from the puzzle, for the tests of every module built on p_08.
+#[cfg(test)]
+pub(crate) const SAMPLE: &str = "\
+162,817,812
+57,618,57
+906,360,560
+592,479,940
+352,342,300
+466,668,158
+542,29,236
+431,825,988
+739,650,466
+52,470,668
+216,146,977
+819,987,18
+117,168,530
+805,96,715
+346,949,466
+970,615,88
+941,993,340
+862,61,35
+984,92,344
+425,690,689
+";
+
 #[cfg(test)]
 mod test {
-    use crate::p_08::{part_1, part_2, read_input};
+    use crate::bigint::BigUint;
+    use crate::p_08::{
+        Chebyshev, Dendrogram, DistanceOverflow, Edge, EdgeStream, KdTree, MST_STRATEGIES,
+        Manhattan, Metric, MstStrategy, ParseError, Part2, Point, Pos, SAMPLE, SquaredEuclidean,
+        UnionFind, WeightedEuclidean, benchmark_mst, build_clusters_1, build_clusters_with,
+        build_mst, build_mst_with, detect_arity, parse_any, parse_points, parse_positions, part_1,
+        part_2, random_points, spanning_tree,
+    };
+
+    /// Brute-force reference: every pair, sorted.
+    fn all_pairs(nodes: &[Pos]) -> Vec<Edge<3>> {
+        let mut edges = Vec::new();
+        for i in 0..nodes.len() {
+            for j in i + 1..nodes.len() {
+                edges.push(Edge::new(nodes[i], nodes[j]));
+            }
+        }
+        edges.sort_unstable();
+        edges
+    }
+
+    fn streamed(nodes: &[Pos]) -> Vec<Edge<3>> {
+        EdgeStream::new(nodes)
+            .map(|(dist, i, j)| {
+                assert!(nodes[i] <= nodes[j]);
+                Edge {
+                    dist,
+                    u: nodes[i],
+                    v: nodes[j],
+                }
+            })
+            .collect()
+    }
+
+    fn lattice(side: i64, spacing: i64) -> Vec<Pos> {
+        let mut nodes = Vec::new();
+        for x in 0..side {
+            for y in 0..side {
+                for z in 0..side {
+                    nodes.push(Pos::new(x * spacing, y * spacing, z * spacing));
+                }
+            }
+        }
+        nodes
+    }
+
+    /// Clusters with their members sorted, so input order does not matter.
+    fn canonical(mut clusters: Vec<Vec<Pos>>) -> Vec<Vec<Pos>> {
+        for cluster in clusters.iter_mut() {
+            cluster.sort();
+        }
+        clusters.sort_by(|a, b| b.len().cmp(&a.len()).then(a.cmp(b)));
+        clusters
+    }
+
+    fn scatter(n: usize, range: i64, seed: u64) -> Vec<Pos> {
+        random_points(n, range, seed)
+    }
+
+    fn top_three(clusters: Vec<Vec<Pos>>) -> usize {
+        clusters.iter().take(3).map(|c| c.len()).product()
+    }
+
+    #[test]
+    fn test_sample_inline() {
+        let nodes = parse_positions(SAMPLE);
+        assert_eq!(top_three(build_clusters_1(&nodes, 10)), 40);
+        let (u, v) = build_mst(&nodes);
+        assert_eq!(u.axis(0) * v.axis(0), 25272);
+    }
+
+    #[test]
+    fn test_union_find_components() {
+        let mut uf = UnionFind::new(7);
+        assert!(uf.union(4, 6));
+        assert!(uf.union(1, 2));
+        assert!(uf.union(2, 6));
+        assert!(!uf.union(1, 4));
+        assert!(uf.union(0, 5));
+        assert_eq!(uf.num_components(), 3);
+        assert_eq!(uf.component_size(6), 4);
+        assert_eq!(uf.component_size(5), 2);
+        assert_eq!(uf.component_size(3), 1);
+        assert_eq!(
+            uf.components_by_size(),
+            vec![vec![1, 2, 4, 6], vec![0, 5], vec![3]]
+        );
+        let members = uf.members();
+        let root = uf.find(4);
+        assert_eq!(members[&root], vec![1, 2, 4, 6]);
+    }
+
+    #[test]
+    fn test_clusters_cover_every_point() {
+        let nodes = parse_positions(SAMPLE);
+        let clusters = build_clusters_1(&nodes, 10);
+        let sizes: Vec<usize> = clusters.iter().map(|c| c.len()).collect();
+        assert_eq!(sizes.iter().sum::<usize>(), nodes.len());
+        assert_eq!(sizes[..3], [5, 4, 2]);
+        assert_eq!(clusters.len(), 11);
+    }
+
+    #[test]
+    fn test_kd_tree_nearest() {
+        let nodes = scatter(300, 1000, 7);
+        let tree = KdTree::new(&nodes);
+        for i in [0, 17, 150, 299] {
+            let mut brute: Vec<(u128, usize)> = (0..nodes.len())
+                .filter(|&j| j != i)
+                .map(|j| (nodes[i].dist(&nodes[j]), j))
+                .collect();
+            brute.sort_unstable_by_key(|&(dist, j)| (dist, nodes[j], j));
+            for k in [1, 5, 64, 299, 500] {
+                let expected = &brute[..k.min(brute.len())];
+                assert_eq!(tree.nearest(i, k), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_edge_stream_matches_all_pairs() {
+        // A small range forces plenty of equal distances and duplicate points.
+        for (n, range) in [(0, 10), (1, 10), (2, 10), (200, 10), (400, 100_000)] {
+            let nodes = scatter(n, range, n as u64 + 1);
+            assert_eq!(streamed(&nodes), all_pairs(&nodes));
+        }
+    }
+
+    #[test]
+    fn test_edge_stream_scales() {
+        // All pairs of 5k points would already be 12.5 million edges.
+        let nodes = scatter(5_000, 100_000, 3);
+        let (u, v) = build_mst(&nodes);
+        assert_ne!(u, v);
+        let first: Vec<_> = EdgeStream::new(&nodes).take(1000).collect();
+        assert!(first.windows(2).all(|w| w[0].0 <= w[1].0));
+    }
+
+    #[test]
+    fn test_edge_order() {
+        let a = Pos::new(0, 0, 0);
+        let b = Pos::new(0, 0, 1);
+        let c = Pos::new(0, 1, 0);
+        let d = Pos::new(-1, 0, 0);
+        assert_eq!(Edge::new(b, a), Edge::new(a, b));
+        assert!(Edge::new(a, b) < Edge::new(a, c));
+        assert!(Edge::new(d, a) < Edge::new(a, b));
+        assert!(Edge::new(a, c) < Edge::new(b, c));
+    }
+
+    #[test]
+    fn test_lattice_ties() {
+        // Every point has up to six neighbours one lattice step (3 units)
+        // away, and every longer distance is shared by many pairs too.
+        let nodes = lattice(5, 3);
+        let edges = streamed(&nodes);
+        assert_eq!(edges, all_pairs(&nodes));
+        assert_eq!(edges[0], Edge::new(Pos::new(0, 0, 0), Pos::new(0, 0, 3)));
+        assert_eq!(edges[1], Edge::new(Pos::new(0, 0, 0), Pos::new(0, 3, 0)));
+        assert_eq!(edges[2], Edge::new(Pos::new(0, 0, 0), Pos::new(3, 0, 0)));
+        assert_eq!(edges[3], Edge::new(Pos::new(0, 0, 3), Pos::new(0, 0, 6)));
+
+        let clusters = canonical(build_clusters_1(&nodes, 40));
+        let last = build_mst(&nodes);
+        // The answers must not depend on how the input happens to be listed.
+        for seed in 1..6u64 {
+            let mut shuffled = nodes.clone();
+            let mut state = seed;
+            for i in (1..shuffled.len()).rev() {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                shuffled.swap(i, (state >> 33) as usize % (i + 1));
+            }
+            assert_eq!(streamed(&shuffled), edges);
+            assert_eq!(canonical(build_clusters_1(&shuffled, 40)), clusters);
+            assert_eq!(build_mst(&shuffled), last);
+        }
+        // The far corner is joined last, through its smallest neighbour.
+        assert_eq!(last, (Pos::new(9, 12, 12), Pos::new(12, 12, 12)));
+    }
 
     #[test]
     fn test_sample_1() {
@@ -332,4 +1368,447 @@ mod test {
         const PATH: &str = "/Users/saranshagarwal/Code/aoc2025/src/p_08.txt";
         println!("The solution is : {}", part_2(PATH));
     }
+
+    #[test]
+    fn test_dendrogram_sample() {
+        let nodes = parse_positions(SAMPLE);
+        let dendrogram = Dendrogram::new(&nodes);
+        assert_eq!(dendrogram.merges.len(), nodes.len() - 1);
+        assert!(dendrogram.merges.windows(2).all(|w| w[0].dist <= w[1].dist));
+        let root = dendrogram.merges.last().unwrap();
+        assert_eq!(root.size, nodes.len());
+        assert_eq!((nodes[root.u], nodes[root.v]), build_mst(&nodes));
+
+        // Ten connections, one of them redundant, leave eleven clusters.
+        let as_points = |clusters: Vec<Vec<usize>>| -> Vec<Vec<Pos>> {
+            clusters
+                .into_iter()
+                .map(|c| c.into_iter().map(|i| nodes[i]).collect())
+                .collect()
+        };
+        let expected = canonical(build_clusters_1(&nodes, 10));
+        assert_eq!(canonical(as_points(dendrogram.cut_into(11))), expected);
+        let tenth = EdgeStream::new(&nodes).nth(9).unwrap().0;
+        assert_eq!(
+            canonical(as_points(dendrogram.cut_at_distance(tenth))),
+            expected
+        );
+
+        assert_eq!(dendrogram.cut_into(1).len(), 1);
+        assert_eq!(dendrogram.cut_into(0).len(), 1);
+        assert_eq!(dendrogram.cut_into(100).len(), nodes.len());
+        assert_eq!(dendrogram.cut_at_distance(0).len(), nodes.len());
+        assert_eq!(dendrogram.cut_at_distance(u128::MAX).len(), 1);
+    }
+
+    #[test]
+    fn test_dendrogram_export() {
+        let nodes = vec![Pos::new(3, 0, 0), Pos::new(0, 0, 0), Pos::new(1, 0, 0)];
+        let dendrogram = Dendrogram::new(&nodes);
+        assert_eq!(
+            dendrogram.to_newick(&nodes),
+            "(('0,0,0':1,'1,0,0':1):3,'3,0,0':4);"
+        );
+        assert_eq!(
+            dendrogram.to_json(&nodes),
+            "{\"leaves\":[[3,0,0],[0,0,0],[1,0,0]],\"merges\":[\
+             {\"left\":1,\"right\":2,\"distance\":\"1\",\"size\":2,\"edge\":[1,2]},\
+             {\"left\":3,\"right\":0,\"distance\":\"4\",\"size\":3,\"edge\":[2,0]}]}"
+        );
+        let far = [Pos::new(0, 0, 0), Pos::new(1 << 40, 0, 0)];
+        assert!(
+            Dendrogram::new(&far)
+                .to_json(&far)
+                .contains("\"distance\":\"1208925819614629174706176\"")
+        );
+        assert_eq!(Dendrogram::new::<3>(&[]).to_newick::<3>(&[]), ";");
+        let single = [Pos::new(1, 2, 3)];
+        assert_eq!(Dendrogram::new(&single).to_newick(&single), "'1,2,3';");
+        assert!(Dendrogram::new(&single).cut_into(1) == vec![vec![0]]);
+    }
+
+    /// Brute-force reference for any metric, in the [`Edge`] order.
+    fn all_pairs_with<const D: usize, M: Metric<D>>(
+        nodes: &[Point<D>],
+        metric: &M,
+    ) -> Vec<(u128, Point<D>, Point<D>)> {
+        let mut edges = Vec::new();
+        for i in 0..nodes.len() {
+            for j in i + 1..nodes.len() {
+                let (u, v) = (nodes[i].min(nodes[j]), nodes[i].max(nodes[j]));
+                edges.push((metric.dist(&u, &v), u, v));
+            }
+        }
+        edges.sort_unstable();
+        edges
+    }
+
+    fn check_metric<const D: usize, M: Metric<D>>(nodes: &[Point<D>], metric: &M) {
+        let streamed: Vec<(u128, Point<D>, Point<D>)> = EdgeStream::with_metric(nodes, metric)
+            .map(|(dist, i, j)| (dist, nodes[i], nodes[j]))
+            .collect();
+        assert_eq!(streamed, all_pairs_with(nodes, metric));
+    }
+
+    #[test]
+    fn test_metrics_match_brute_force() {
+        for (n, range) in [(150, 8), (300, 10_000)] {
+            let nodes = scatter(n, range, 11);
+            check_metric(&nodes, &SquaredEuclidean);
+            check_metric(&nodes, &Manhattan);
+            check_metric(&nodes, &Chebyshev);
+            check_metric(&nodes, &WeightedEuclidean::new([1, 4, 0]));
+        }
+    }
+
+    #[test]
+    fn test_metrics_disagree() {
+        let a = Pos::new(0, 0, 0);
+        let b = Pos::new(3, 3, 0);
+        let c = Pos::new(5, 0, 0);
+        assert_eq!(SquaredEuclidean.dist(&a, &b), 18);
+        assert_eq!(Manhattan.dist(&a, &b), 6);
+        assert_eq!(Chebyshev.dist(&a, &b), 3);
+        assert_eq!(WeightedEuclidean::new([2, 1, 5]).dist(&a, &b), 27);
+
+        // `a`'s nearest neighbour is `b` in straight lines but `c` on a grid.
+        let nodes = [a, b, c];
+        let tree = KdTree::new(&nodes);
+        assert_eq!(tree.nearest(0, 1), vec![(18, 1)]);
+        assert_eq!(
+            KdTree::with_metric(&nodes, &Manhattan).nearest(0, 1),
+            vec![(5, 2)]
+        );
+        assert_eq!(
+            KdTree::with_metric(&nodes, &Chebyshev).nearest(0, 1),
+            vec![(3, 1)]
+        );
+        let tall = WeightedEuclidean::new([1, 10, 1]);
+        assert_eq!(
+            KdTree::with_metric(&nodes, &tall).nearest(0, 1),
+            vec![(25, 2)]
+        );
+
+        assert_eq!(build_mst(&nodes), (a, b));
+        assert_eq!(build_mst_with(&nodes, &Manhattan), (b, c));
+        assert_eq!(build_mst_with(&nodes, &tall), (b, c));
+
+        // A line of points along x and one off to the side along y: the
+        // first connection depends on which axis the metric cares about.
+        let nodes = [
+            Pos::new(0, 0, 0),
+            Pos::new(4, 0, 0),
+            Pos::new(0, 3, 0),
+            Pos::new(20, 20, 20),
+        ];
+        let first = |clusters: Vec<Vec<Pos>>| clusters[0].clone();
+        assert_eq!(first(build_clusters_1(&nodes, 1)), vec![nodes[0], nodes[2]]);
+        assert_eq!(
+            first(build_clusters_with(
+                &nodes,
+                1,
+                &WeightedEuclidean::new([1, 4, 1])
+            )),
+            vec![nodes[0], nodes[1]]
+        );
+        let sizes = |clusters: Vec<Vec<Pos>>| clusters.iter().map(|c| c.len()).collect::<Vec<_>>();
+        assert_eq!(
+            sizes(build_clusters_with(&nodes, 3, &Chebyshev)),
+            vec![3, 1]
+        );
+        assert_eq!(
+            Dendrogram::with_metric(&nodes, &Manhattan)
+                .merges
+                .last()
+                .unwrap()
+                .dist,
+            Manhattan.dist(&nodes[1], &nodes[3])
+        );
+    }
+
+    #[test]
+    fn test_parse_any_dimension() {
+        assert_eq!(detect_arity("1,2\n3,4\n\n5,6\n"), Ok(2));
+        assert_eq!(detect_arity(SAMPLE), Ok(3));
+        assert_eq!(detect_arity(""), Ok(0));
+        assert_eq!(
+            parse_points::<2>("1,2\n-3, 4\n"),
+            Ok(vec![
+                Point::from_coords([1, 2]),
+                Point::from_coords([-3, 4])
+            ])
+        );
+        assert_eq!(
+            parse_points::<5>("1,2,3,4,5"),
+            Ok(vec![Point::from_coords([1, 2, 3, 4, 5])])
+        );
+        assert_eq!(parse_points::<3>(SAMPLE).unwrap(), parse_positions(SAMPLE));
+    }
+
+    #[test]
+    fn test_parse_arity_errors() {
+        let ragged = "1,2,3\n4,5,6\n7,8\n";
+        let err = ParseError::Arity {
+            line: 3,
+            expected: 3,
+            found: 2,
+        };
+        assert_eq!(detect_arity(ragged), Err(err.clone()));
+        assert_eq!(parse_points::<3>(ragged), Err(err.clone()));
+        assert_eq!(err.to_string(), "line 3: expected 3 coordinates, found 2");
+        assert_eq!(
+            parse_points::<2>("1,2\n3,x\n"),
+            Err(ParseError::InvalidCoordinate {
+                line: 2,
+                value: "x".to_string()
+            })
+        );
+        assert_eq!(
+            parse_points::<4>("1,2,3\n").unwrap_err().to_string(),
+            "line 1: expected 4 coordinates, found 3"
+        );
+    }
+
+    #[test]
+    fn test_parts_dispatch_on_dimension() {
+        let dir = std::env::temp_dir();
+        let flat = dir.join("aoc2025_p_08_2d.txt");
+        let hyper = dir.join("aoc2025_p_08_4d.txt");
+        std::fs::write(&flat, "0,0\n1,0\n10,0\n11,0\n30,0\n").unwrap();
+        std::fs::write(&hyper, "0,1,2,3\n1,1,2,3\n10,1,2,3\n11,1,2,3\n30,1,2,3\n").unwrap();
+        for path in [flat, hyper] {
+            let path = path.to_str().unwrap();
+            // {0, 1}, {10, 11} and {30}; the last MST edge joins 11 and 30.
+            assert_eq!(part_1(path, 2), 4);
+            assert_eq!(part_2(path), 330);
+        }
+
+        assert_eq!(
+            parse_any("1,2,3,4,5,6,7,8,9\n", Part2),
+            Err(ParseError::UnsupportedDimension { found: 9 })
+        );
+        assert_eq!(
+            parse_any("1,2\n3,4,5\n", Part2).unwrap_err().to_string(),
+            "line 2: expected 2 coordinates, found 3"
+        );
+    }
+
+    #[test]
+    fn test_part_2_extreme_coordinates() {
+        let path = std::env::temp_dir().join("aoc2025_p_08_extremes.txt");
+        std::fs::write(&path, format!("{},0\n{},0\n", i64::MIN, i64::MAX)).unwrap();
+        assert_eq!(
+            part_2(path.to_str().unwrap()),
+            i64::MIN as i128 * i64::MAX as i128
+        );
+    }
+
+    #[test]
+    fn test_other_dimensions() {
+        check_metric(&random_points::<1>(80, 50, 1), &SquaredEuclidean);
+        check_metric(&random_points::<2>(200, 30, 2), &SquaredEuclidean);
+        check_metric(&random_points::<2>(200, 30, 2), &Manhattan);
+        check_metric(&random_points::<4>(200, 6, 3), &Chebyshev);
+        check_metric(&random_points::<6>(150, 1000, 4), &SquaredEuclidean);
+        check_metric(
+            &random_points::<4>(150, 20, 5),
+            &WeightedEuclidean::new([1, 2, 3, 0]),
+        );
+
+        // Two tight groups on a plane, far apart.
+        let plane = parse_points::<2>("0,0\n1,0\n0,1\n100,100\n101,100\n").unwrap();
+        let sizes: Vec<usize> = build_clusters_with(&plane, 3, &SquaredEuclidean)
+            .iter()
+            .map(|c| c.len())
+            .collect();
+        assert_eq!(sizes, vec![3, 2]);
+        assert_eq!(
+            build_mst_with(&plane, &SquaredEuclidean),
+            (Point::from_coords([0, 1]), Point::from_coords([100, 100]))
+        );
+        assert_eq!(
+            Dendrogram::new(&plane).to_newick(&plane),
+            "((('0,0':1,'0,1':1):0,'1,0':1):19800,('100,100':1,'101,100':1):19800);"
+        );
+
+        let hyper = random_points::<4>(60, 100, 9);
+        let dendrogram = Dendrogram::new(&hyper);
+        assert_eq!(dendrogram.merges.len(), 59);
+        assert_eq!(dendrogram.cut_into(5).len(), 5);
+    }
+
+    #[test]
+    fn test_extreme_coordinates() {
+        let (min, max) = (i64::MIN, i64::MAX);
+        let line = [
+            Point::from_coords([min]),
+            Point::from_coords([max]),
+            Point::from_coords([0]),
+        ];
+        let span = u64::MAX as u128;
+        assert_eq!(line[0].dist(&line[1]), span * span);
+        assert_eq!(line[0].dist(&line[2]), 1 << 126);
+        assert_eq!(line[1].dist(&line[2]), (max as u128).pow(2));
+        // In i64 these would all have wrapped and sorted in the wrong order.
+        let edges: Vec<(u128, usize, usize)> = EdgeStream::new(&line).collect();
+        assert_eq!(
+            edges,
+            vec![
+                ((max as u128).pow(2), 2, 1),
+                (1 << 126, 0, 2),
+                (span * span, 0, 1)
+            ]
+        );
+        assert_eq!(build_mst_with(&line, &SquaredEuclidean), (line[0], line[2]));
+        assert_eq!(Manhattan.dist(&line[0], &line[1]), span);
+        assert_eq!(Chebyshev.dist(&line[0], &line[1]), span);
+
+        // Puzzle-shaped input whose squares overflow i64 but not u128.
+        let far: Vec<Pos> = scatter(200, 1 << 20, 5)
+            .into_iter()
+            .map(|p| Pos::new(p.axis(0) << 40, p.axis(1) << 40, -(p.axis(2) << 40)))
+            .collect();
+        check_metric(&far, &SquaredEuclidean);
+        check_metric(&far, &WeightedEuclidean::new([3, 1, 2]));
+    }
+
+    #[test]
+    fn test_distance_overflow() {
+        let (min, max) = (i64::MIN, i64::MAX);
+        let corners = [Pos::new(min, min, min), Pos::new(max, max, max)];
+        assert_eq!(corners[0].checked_dist(&corners[1]), None);
+        let err = EdgeStream::try_with_metric(&corners, &SquaredEuclidean)
+            .err()
+            .unwrap();
+        assert_eq!(
+            err,
+            DistanceOverflow {
+                from: format!("{},{},{}", min, min, min),
+                to: format!("{},{},{}", max, max, max),
+            }
+        );
+        assert!(err.to_string().contains("does not fit in a u128"));
+        // The same points are fine under metrics that do not square.
+        assert!(EdgeStream::try_with_metric(&corners, &Manhattan).is_ok());
+        assert!(EdgeStream::try_with_metric(&corners, &Chebyshev).is_ok());
+
+        // Two axes at full span overflow, one axis from the origin does not.
+        let plane = [
+            Point::from_coords([min, min]),
+            Point::from_coords([max, max]),
+        ];
+        assert!(KdTree::try_with_metric(&plane, &SquaredEuclidean).is_err());
+        let half = [Point::from_coords([0, 0]), Point::from_coords([max, max])];
+        assert!(KdTree::try_with_metric(&half, &SquaredEuclidean).is_ok());
+        let heavy = WeightedEuclidean::new([u64::MAX, 1]);
+        assert!(KdTree::try_with_metric(&half, &heavy).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "does not fit in a u128")]
+    fn test_overflow_panics_in_infallible_api() {
+        let corners = [
+            Pos::new(i64::MIN, i64::MIN, 0),
+            Pos::new(i64::MAX, i64::MAX, 0),
+        ];
+        build_mst(&corners);
+    }
+
+    #[test]
+    fn test_mst_strategies_agree() {
+        let sample = parse_positions(SAMPLE);
+        let inputs = [
+            sample.clone(),
+            scatter(300, 12, 8),
+            scatter(300, 1_000_000, 9),
+            lattice(4, 2),
+            vec![],
+            vec![Pos::new(1, 1, 1)],
+            vec![Pos::new(1, 1, 1); 4],
+        ];
+        for nodes in &inputs {
+            let trees: Vec<_> = MST_STRATEGIES
+                .iter()
+                .map(|&s| spanning_tree(nodes, &SquaredEuclidean, s).unwrap())
+                .collect();
+            assert_eq!(trees[0].edges.len(), nodes.len().saturating_sub(1));
+            assert_eq!(trees[1], trees[0]);
+            assert_eq!(trees[2], trees[0]);
+            if nodes.len() > 1 {
+                let &(_, i, j) = trees[0].edges.last().unwrap();
+                assert_eq!((nodes[i], nodes[j]), build_mst(nodes));
+            }
+        }
+
+        let tree = spanning_tree(&sample, &Manhattan, MstStrategy::Prim).unwrap();
+        let kruskal = spanning_tree(&sample, &Manhattan, MstStrategy::default()).unwrap();
+        assert_eq!(tree, kruskal);
+        let weight: u128 = tree.edges.iter().map(|e| e.0).sum();
+        assert_eq!(tree.total, BigUint::from(weight));
+    }
+
+    #[test]
+    fn test_mst_total_beyond_u128() {
+        // A square whose side squared fits in a u128 and whose diagonal
+        // squared still does, but three sides together do not.
+        let a = 6_000_000_000_000_000_000;
+        let nodes: Vec<Point<2>> = [(-a, -a), (a, -a), (-a, a), (a, a)]
+            .into_iter()
+            .map(|(x, y)| Point::from_coords([x, y]))
+            .collect();
+        let side = (2 * a as u128).pow(2);
+        for strategy in MST_STRATEGIES {
+            let tree = spanning_tree(&nodes, &SquaredEuclidean, strategy).unwrap();
+            assert_eq!(tree.edges.len(), 3);
+            assert!(tree.edges.iter().all(|e| e.0 == side));
+            assert_eq!(tree.total.to_u128(), None);
+            assert_eq!(tree.total, &BigUint::from(side) * &BigUint::from(3u128));
+        }
+        let corners = [
+            Pos::new(i64::MIN, i64::MIN, i64::MIN),
+            Pos::new(i64::MAX, i64::MAX, i64::MAX),
+        ];
+        for strategy in MST_STRATEGIES {
+            assert!(spanning_tree(&corners, &SquaredEuclidean, strategy).is_err());
+        }
+    }
+
+    #[test]
+    fn test_benchmark_strategies_agree() {
+        assert!(benchmark_mst(300).is_ok());
+    }
+
+    #[test]
+    fn test_mst_export() {
+        let nodes = vec![Pos::new(3, 0, 0), Pos::new(0, 0, 0), Pos::new(1, 0, 0)];
+        let tree = spanning_tree(&nodes, &SquaredEuclidean, MstStrategy::Prim).unwrap();
+        assert_eq!(tree.edges, vec![(1, 1, 2), (4, 2, 0)]);
+        assert_eq!(tree.total, BigUint::from(5u128));
+        assert_eq!(
+            tree.to_dot(&nodes),
+            "graph mst {
+  0 [label=\"3,0,0\"];
+  1 [label=\"0,0,0\"];
+  2 [label=\"1,0,0\"];
+  1 -- 2 [label=\"1\"];
+  2 -- 0 [label=\"4\"];
+}
+"
+        );
+        assert_eq!(
+            tree.to_csv(&nodes),
+            "u,v,u_pos,v_pos,distance
+1,2,\"0,0,0\",\"1,0,0\",1
+2,0,\"1,0,0\",\"3,0,0\",4
+"
+        );
+        let graphml = tree.to_graphml(&nodes);
+        assert!(graphml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<graphml"));
+        assert!(graphml.contains("    <node id=\"n0\"><data key=\"pos\">3,0,0</data></node>\n"));
+        assert!(graphml.contains(
+            "    <edge source=\"n2\" target=\"n0\"><data key=\"dist\">4</data></edge>\n"
+        ));
+        assert!(graphml.ends_with("  </graph>\n</graphml>\n"));
+        assert_eq!(graphml.matches("<edge ").count(), 2);
+    }
 }
@@ -1,8 +1,8 @@
 // I asked AI to generate a cute visualisation for the problem 4
 
-use gif::{Encoder, Frame, Repeat};
-use image::{ImageBuffer, Rgb, RgbImage};
-use std::fs::{File, read_to_string};
+use crate::viz::{self, CellStyle, FrameSource, Palette};
+use image::{Rgb, RgbImage};
+use std::fs::read_to_string;
 
 const DIRS: [(i32, i32); 8] = [
     (-1, -1),
@@ -21,6 +21,14 @@ const COLOR_ROCK_SAFE: Rgb<u8> = Rgb([68, 255, 68]); // Bright green
 const COLOR_ROCK_DANGER: Rgb<u8> = Rgb([255, 170, 0]); // Orange
 const COLOR_ROCK_DYING: Rgb<u8> = Rgb([255, 68, 68]); // Red
 const COLOR_GRID: Rgb<u8> = Rgb([50, 50, 50]); // Dark gray
+const COLOR_BACKGROUND: Rgb<u8> = Rgb([26, 26, 26]); // Dark background
+
+// Palette indices, in the order `palette` adds the colors
+const INDEX_EMPTY: u8 = 0;
+const INDEX_ROCK_SAFE: u8 = 1;
+const INDEX_ROCK_DANGER: u8 = 2;
+const INDEX_ROCK_DYING: u8 = 3;
+const INDEX_GRID: u8 = 4;
 
 type Grid = Vec<Vec<char>>;
 
@@ -87,9 +95,70 @@ fn modify_grid(mut grid: Grid, cells_to_remove: &[(usize, usize)]) -> Grid {
     grid
 }
 
-fn simulate_evolution(input_path: &str, max_iterations: usize) -> Vec<GridState> {
-    let mut grid = read_input(input_path);
-    let mut states = Vec::new();
+/// What a finished simulation needs to keep: the starting grid and the cells
+/// removed after each iteration. Any state can be rebuilt from it, so memory
+/// stays bounded by the grid size rather than grid size × iterations.
+struct History {
+    initial: Grid,
+    removals: Vec<Vec<(usize, usize)>>,
+}
+
+impl History {
+    fn len(&self) -> usize {
+        self.removals.len()
+    }
+
+    /// Rock count at the start of every iteration.
+    fn rock_counts(&self) -> Vec<usize> {
+        let initial: usize = self
+            .initial
+            .iter()
+            .map(|row| row.iter().filter(|&&c| c == '@').count())
+            .sum();
+        self.removals
+            .iter()
+            .scan(initial, |rocks, removed| {
+                let current = *rocks;
+                *rocks -= removed.len();
+                Some(current)
+            })
+            .collect()
+    }
+
+    /// Replays the removals to rebuild the states at `iterations`, which must
+    /// be ascending.
+    fn states_at(&self, iterations: &[usize]) -> Vec<GridState> {
+        let mut grid = self.initial.clone();
+        let mut applied = 0;
+        let mut states = Vec::with_capacity(iterations.len());
+        for &iteration in iterations {
+            for removed in &self.removals[applied..iteration] {
+                grid = modify_grid(grid, removed);
+            }
+            applied = iteration;
+            let (neighbor_counts, cells_to_remove) = enhance_grid(&grid);
+            states.push(GridState {
+                grid: grid.clone(),
+                neighbor_counts,
+                cells_to_remove,
+                iteration,
+            });
+        }
+        states
+    }
+}
+
+/// Runs the simulation, handing each state to `on_state` before moving on.
+/// Only the current state is alive at any time.
+fn evolve(
+    mut grid: Grid,
+    max_iterations: usize,
+    mut on_state: impl FnMut(&GridState) -> Result<(), Box<dyn std::error::Error>>,
+) -> Result<History, Box<dyn std::error::Error>> {
+    let mut history = History {
+        initial: grid.clone(),
+        removals: Vec::new(),
+    };
 
     println!(
         "Starting simulation on {}x{} grid...",
@@ -103,97 +172,143 @@ fn simulate_evolution(input_path: &str, max_iterations: usize) -> Vec<GridState>
         }
 
         let (neighbor_counts, cells_to_remove) = enhance_grid(&grid);
-
-        states.push(GridState {
-            grid: grid.clone(),
+        let state = GridState {
+            grid,
             neighbor_counts,
-            cells_to_remove: cells_to_remove.clone(),
+            cells_to_remove,
             iteration,
-        });
+        };
+        on_state(&state)?;
+
+        let converged = state.cells_to_remove.is_empty();
+        grid = modify_grid(state.grid, &state.cells_to_remove);
+        history.removals.push(state.cells_to_remove);
 
-        if cells_to_remove.is_empty() {
+        if converged {
             println!("Converged at iteration {}", iteration);
             break;
         }
+    }
+
+    Ok(history)
+}
+
+/// Renders and encodes every frame while the simulation runs instead of
+/// collecting the states first.
+fn stream_evolution(
+    grid: Grid,
+    max_iterations: usize,
+    cell_size: u32,
+    output_gif: Option<&str>,
+    frame_delay: u16,
+    compare_sizes: bool,
+) -> Result<History, Box<dyn std::error::Error>> {
+    let style = cell_style(cell_size);
+    let mut writer = match output_gif {
+        Some(path) => {
+            let (n, m) = (grid.len() as u32, grid[0].len() as u32);
+            let (width, height) = (m * cell_size, n * cell_size);
+            println!("Streaming GIF animation to {}...", path);
+            println!(
+                "Frame size: {}x{} ({}x{} cells at {} pixels each)",
+                width, height, m, n, cell_size
+            );
+            Some(gif_writer(path, width, height, frame_delay, compare_sizes)?)
+        }
+        None => None,
+    };
+
+    let history = evolve(grid, max_iterations, |state| {
+        if let Some(writer) = writer.as_mut() {
+            if state.iteration % 5 == 0 {
+                println!("  Encoding frame {}...", state.iteration + 1);
+            }
+            let states = std::slice::from_ref(state);
+            writer.write_indexed(viz::render_indexed(&Evolution { states }, 0, style))?;
+        }
+        Ok(())
+    })?;
 
-        grid = modify_grid(grid, &cells_to_remove);
+    if let (Some(writer), Some(path)) = (writer, output_gif) {
+        println!("GIF animation saved to {}: {}", path, writer.finish()?);
     }
+    Ok(history)
+}
+
+/// A delta-encoded GIF, optionally also measured as full frames.
+fn gif_writer(
+    path: &str,
+    width: u32,
+    height: u32,
+    frame_delay: u16,
+    compare_sizes: bool,
+) -> Result<viz::GifWriter, Box<dyn std::error::Error>> {
+    let palette = palette();
+    let writer = viz::GifWriter::create(path, width, height, &palette, frame_delay)?;
+    if compare_sizes {
+        writer.with_full_frame_comparison(&palette)
+    } else {
+        Ok(writer)
+    }
+}
+
+fn palette() -> Palette {
+    let mut palette = Palette::new();
+    for color in [
+        COLOR_EMPTY,
+        COLOR_ROCK_SAFE,
+        COLOR_ROCK_DANGER,
+        COLOR_ROCK_DYING,
+        COLOR_GRID,
+        COLOR_BACKGROUND,
+    ] {
+        palette.add(color);
+    }
+    palette
+}
 
-    states
+fn cell_style(cell_size: u32) -> CellStyle {
+    CellStyle::new(cell_size).with_grid_line(INDEX_GRID)
 }
 
-fn get_cell_color(state: &GridState, x: usize, y: usize) -> Rgb<u8> {
+fn get_cell_index(state: &GridState, x: usize, y: usize) -> u8 {
     let cell = state.grid[x][y];
 
     if cell == '.' {
-        return COLOR_EMPTY;
+        return INDEX_EMPTY;
     }
 
     if cell == '@' {
         let count = state.neighbor_counts[x][y];
         if count < 2 {
-            COLOR_ROCK_DYING // Very vulnerable
+            INDEX_ROCK_DYING // Very vulnerable
         } else if count < 4 {
-            COLOR_ROCK_DANGER // Will be removed
+            INDEX_ROCK_DANGER // Will be removed
         } else {
-            COLOR_ROCK_SAFE // Safe
+            INDEX_ROCK_SAFE // Safe
         }
     } else {
-        COLOR_EMPTY
+        INDEX_EMPTY
     }
 }
 
-fn create_frame(state: &GridState, cell_size: u32) -> RgbImage {
-    let n = state.grid.len() as u32;
-    let m = state.grid[0].len() as u32;
-    let width = m * cell_size;
-    let height = n * cell_size;
-
-    let mut img = ImageBuffer::new(width, height);
+/// The recorded states, one frame each.
+struct Evolution<'a> {
+    states: &'a [GridState],
+}
 
-    // Fill background
-    for pixel in img.pixels_mut() {
-        *pixel = COLOR_EMPTY;
+impl FrameSource for Evolution<'_> {
+    fn grid_size(&self) -> (usize, usize) {
+        (self.states[0].grid.len(), self.states[0].grid[0].len())
     }
 
-    // Draw cells
-    for i in 0..n as usize {
-        for j in 0..m as usize {
-            let color = get_cell_color(state, i, j);
-
-            // Fill cell
-            for dy in 0..cell_size {
-                for dx in 0..cell_size {
-                    let px = j as u32 * cell_size + dx;
-                    let py = i as u32 * cell_size + dy;
-
-                    // Draw grid lines
-                    if dx == 0 || dy == 0 {
-                        if cell_size > 2 {
-                            img.put_pixel(px, py, COLOR_GRID);
-                        } else {
-                            img.put_pixel(px, py, color);
-                        }
-                    } else {
-                        img.put_pixel(px, py, color);
-                    }
-                }
-            }
-        }
+    fn frame_count(&self) -> usize {
+        self.states.len()
     }
 
-    img
-}
-
-fn save_frame_as_png(
-    state: &GridState,
-    cell_size: u32,
-    path: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let img = create_frame(state, cell_size);
-    img.save(path)?;
-    println!("Saved frame to {}", path);
-    Ok(())
+    fn cell(&self, frame: usize, row: usize, col: usize) -> u8 {
+        get_cell_index(&self.states[frame], row, col)
+    }
 }
 
 fn create_gif_animation(
@@ -201,11 +316,12 @@ fn create_gif_animation(
     cell_size: u32,
     output_path: &str,
     frame_delay: u16,
+    compare_sizes: bool,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let n = states[0].grid.len() as u32;
-    let m = states[0].grid[0].len() as u32;
-    let width = m * cell_size;
-    let height = n * cell_size;
+    let evolution = Evolution { states };
+    let style = cell_style(cell_size);
+    let (width, height) = viz::frame_size(&evolution, style);
+    let (n, m) = evolution.grid_size();
 
     println!("Creating GIF animation with {} frames...", states.len());
     println!(
@@ -213,76 +329,25 @@ fn create_gif_animation(
         width, height, m, n, cell_size
     );
 
-    // Create a color palette for the GIF
-    let mut palette = Vec::new();
-    palette.extend_from_slice(&COLOR_EMPTY.0); // Index 0
-    palette.extend_from_slice(&COLOR_ROCK_SAFE.0); // Index 1
-    palette.extend_from_slice(&COLOR_ROCK_DANGER.0); // Index 2
-    palette.extend_from_slice(&COLOR_ROCK_DYING.0); // Index 3
-    palette.extend_from_slice(&COLOR_GRID.0); // Index 4
-    palette.extend_from_slice(&[26, 26, 26]); // Index 5 (dark background)
-
-    // Pad palette to 256 colors (GIF requirement)
-    while palette.len() < 256 * 3 {
-        palette.extend_from_slice(&[0, 0, 0]);
-    }
-
-    let file = File::create(output_path)?;
-    let mut encoder = Encoder::new(file, width as u16, height as u16, &palette)?;
-    encoder.set_repeat(Repeat::Infinite)?;
-
-    for (idx, state) in states.iter().enumerate() {
+    let mut writer = gif_writer(output_path, width, height, frame_delay, compare_sizes)?;
+    for idx in 0..states.len() {
         if idx % 5 == 0 {
             println!("  Encoding frame {}/{}...", idx + 1, states.len());
         }
-
-        let img = create_frame(state, cell_size);
-
-        // Convert RGB image to indexed color using our palette
-        let indexed_pixels: Vec<u8> = img
-            .pixels()
-            .map(|pixel| {
-                // Map pixel color to palette index
-                let rgb = [pixel[0], pixel[1], pixel[2]];
-                if rgb == COLOR_EMPTY.0 {
-                    0
-                } else if rgb == COLOR_ROCK_SAFE.0 {
-                    1
-                } else if rgb == COLOR_ROCK_DANGER.0 {
-                    2
-                } else if rgb == COLOR_ROCK_DYING.0 {
-                    3
-                } else if rgb == COLOR_GRID.0 {
-                    4
-                } else {
-                    0
-                } // default to empty
-            })
-            .collect();
-
-        let mut frame = Frame::default();
-        frame.width = width as u16;
-        frame.height = height as u16;
-        frame.delay = frame_delay;
-        frame.buffer = std::borrow::Cow::Owned(indexed_pixels);
-
-        encoder.write_frame(&frame)?;
+        writer.write_indexed(viz::render_indexed(&evolution, idx, style))?;
     }
 
-    println!("GIF animation saved to {}", output_path);
+    println!(
+        "GIF animation saved to {}: {}",
+        output_path,
+        writer.finish()?
+    );
     Ok(())
 }
 
-fn create_summary_image(
-    states: &[GridState],
-    cell_size: u32,
-    output_path: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let num_states = states.len();
-    let num_frames = 6.min(num_states);
-
-    // Select frames to display
-    let selected_indices: Vec<usize> = if num_states <= 6 {
+/// Iterations shown in the summary image.
+fn summary_indices(num_states: usize) -> Vec<usize> {
+    if num_states <= 6 {
         (0..num_states).collect()
     } else {
         vec![
@@ -293,104 +358,88 @@ fn create_summary_image(
             num_states - 2,
             num_states - 1,
         ]
-    };
-
-    let frame_width = states[0].grid[0].len() as u32 * cell_size;
-    let frame_height = states[0].grid.len() as u32 * cell_size;
-    let padding = 10u32;
-
-    let cols = 3u32;
-    let rows = (num_frames as u32 + cols - 1) / cols;
-
-    let total_width = cols * frame_width + (cols + 1) * padding;
-    let total_height = rows * frame_height + (rows + 1) * padding;
-
-    let mut summary_img = ImageBuffer::new(total_width, total_height);
-
-    // Fill background with dark color
-    for pixel in summary_img.pixels_mut() {
-        *pixel = Rgb([26, 26, 26]);
     }
+}
 
+/// Tiles `key_states` three to a row.
+fn create_summary_image(
+    key_states: &[GridState],
+    cell_size: u32,
+    output_path: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
     println!(
         "Creating summary image with {} key frames...",
-        selected_indices.len()
+        key_states.len()
     );
 
-    for (idx, &state_idx) in selected_indices.iter().enumerate() {
-        let row = idx as u32 / cols;
-        let col = idx as u32 % cols;
-
-        let x_offset = (col + 1) * padding + col * frame_width;
-        let y_offset = (row + 1) * padding + row * frame_height;
-
-        let frame = create_frame(&states[state_idx], cell_size);
-
-        // Copy frame to summary image
-        for y in 0..frame_height {
-            for x in 0..frame_width {
-                let pixel = frame.get_pixel(x, y);
-                summary_img.put_pixel(x_offset + x, y_offset + y, *pixel);
-            }
-        }
-    }
+    let evolution = Evolution { states: key_states };
+    let palette = palette();
+    let frames: Vec<RgbImage> = (0..key_states.len())
+        .map(|idx| viz::render_rgb(&evolution, idx, cell_style(cell_size), &palette))
+        .collect();
 
+    let summary_img = viz::tile_images(&frames, 3, 10, COLOR_BACKGROUND);
     summary_img.save(output_path)?;
     println!("Summary image saved to {}", output_path);
     Ok(())
 }
 
-fn print_stats(states: &[GridState]) {
+fn print_stats(history: &History) {
+    let rock_counts = history.rock_counts();
+    let initial_rocks = rock_counts[0];
+    let final_rocks = *rock_counts.last().unwrap();
+
     println!("\n{}", "=".repeat(60));
     println!("EVOLUTION STATISTICS");
     println!("{}", "=".repeat(60));
-    println!("Total iterations: {}", states.len());
+    println!("Total iterations: {}", history.len());
     println!(
         "Grid size: {}x{}",
-        states[0].grid.len(),
-        states[0].grid[0].len()
+        history.initial.len(),
+        history.initial[0].len()
     );
 
-    let initial_rocks: usize = states[0]
-        .grid
-        .iter()
-        .map(|row| row.iter().filter(|&&c| c == '@').count())
-        .sum();
-
-    let final_rocks: usize = states
-        .last()
-        .unwrap()
-        .grid
-        .iter()
-        .map(|row| row.iter().filter(|&&c| c == '@').count())
-        .sum();
-
     println!("Initial rock cells: {}", initial_rocks);
     println!("Final rock cells: {}", final_rocks);
     println!("Cells made accessible: {}", initial_rocks - final_rocks);
 
     println!("\nFirst 10 iterations:");
-    for state in states.iter().take(10) {
-        let rock_count: usize = state
-            .grid
-            .iter()
-            .map(|row| row.iter().filter(|&&c| c == '@').count())
-            .sum();
+    for (iteration, (rock_count, removed)) in rock_counts
+        .iter()
+        .zip(&history.removals)
+        .enumerate()
+        .take(10)
+    {
         println!(
             "  Iteration {}: {} rocks, {} will be removed",
-            state.iteration,
+            iteration,
             rock_count,
-            state.cells_to_remove.len()
+            removed.len()
         );
     }
 
-    if states.len() > 10 {
-        println!("  ... ({} more iterations)", states.len() - 10);
+    if history.len() > 10 {
+        println!("  ... ({} more iterations)", history.len() - 10);
     }
 
     println!("{}", "=".repeat(60));
 }
 
+/// How `visualize` gets from the simulation to the output files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Pipeline {
+    /// Stream once the grid has more than `STREAMING_THRESHOLD` cells.
+    #[default]
+    Auto,
+    /// Keep every state in memory, then encode.
+    Buffered,
+    /// Encode each frame as it is simulated and rebuild the summary frames
+    /// from the removal history.
+    Streaming,
+}
+
+const STREAMING_THRESHOLD: usize = 64 * 64;
+
 pub fn visualize(
     input_path: &str,
     output_gif: Option<&str>,
@@ -399,15 +448,72 @@ pub fn visualize(
     frame_delay: u16,
     max_iterations: usize,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let states = simulate_evolution(input_path, max_iterations);
-    print_stats(&states);
+    visualize_with(
+        input_path,
+        output_gif,
+        output_summary,
+        cell_size,
+        frame_delay,
+        max_iterations,
+        Pipeline::Auto,
+        false,
+    )
+}
 
-    if let Some(gif_path) = output_gif {
-        create_gif_animation(&states, cell_size, gif_path, frame_delay)?;
-    }
+/// Like `visualize`. `compare_sizes` also encodes the GIF with full frames
+/// to report how much the delta encoding saved, at twice the encoding time.
+#[allow(clippy::too_many_arguments)]
+pub fn visualize_with(
+    input_path: &str,
+    output_gif: Option<&str>,
+    output_summary: Option<&str>,
+    cell_size: u32,
+    frame_delay: u16,
+    max_iterations: usize,
+    pipeline: Pipeline,
+    compare_sizes: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let grid = read_input(input_path);
+    let streaming = match pipeline {
+        Pipeline::Auto => grid.len() * grid[0].len() > STREAMING_THRESHOLD,
+        Pipeline::Buffered => false,
+        Pipeline::Streaming => true,
+    };
 
-    if let Some(summary_path) = output_summary {
-        create_summary_image(&states, cell_size, summary_path)?;
+    if streaming {
+        let history = stream_evolution(
+            grid,
+            max_iterations,
+            cell_size,
+            output_gif,
+            frame_delay,
+            compare_sizes,
+        )?;
+        print_stats(&history);
+
+        if let Some(summary_path) = output_summary {
+            let key_states = history.states_at(&summary_indices(history.len()));
+            create_summary_image(&key_states, cell_size, summary_path)?;
+        }
+    } else {
+        let mut states = Vec::new();
+        let history = evolve(grid, max_iterations, |state| {
+            states.push(state.clone());
+            Ok(())
+        })?;
+        print_stats(&history);
+
+        if let Some(gif_path) = output_gif {
+            create_gif_animation(&states, cell_size, gif_path, frame_delay, compare_sizes)?;
+        }
+
+        if let Some(summary_path) = output_summary {
+            let key_states: Vec<GridState> = summary_indices(states.len())
+                .into_iter()
+                .map(|idx| states[idx].clone())
+                .collect();
+            create_summary_image(&key_states, cell_size, summary_path)?;
+        }
     }
 
     println!("\n✓ Visualization complete!");
@@ -419,6 +525,33 @@ pub fn visualize(
 mod tests {
     use super::*;
 
+    impl History {
+        fn from_states(states: &[GridState]) -> Self {
+            Self {
+                initial: states[0].grid.clone(),
+                removals: states.iter().map(|s| s.cells_to_remove.clone()).collect(),
+            }
+        }
+    }
+
+    fn simulate_evolution(input_path: &str, max_iterations: usize) -> Vec<GridState> {
+        let mut states = Vec::new();
+        evolve(read_input(input_path), max_iterations, |state| {
+            states.push(state.clone());
+            Ok(())
+        })
+        .expect("Collecting states cannot fail");
+        states
+    }
+
+    fn create_frame(state: &GridState, cell_size: u32) -> RgbImage {
+        let states = std::slice::from_ref(state);
+        viz::render_rgb(&Evolution { states }, 0, cell_style(cell_size), &palette())
+    }
+
+    const SAMPLE: &str = "..@@.@@@@.\n@@@.@.@.@@\n@@@@@.@.@@\n@.@@@@..@.\n@@.@@@@.@@\n\
+                          .@@@@@@@.@\n.@.@.@.@@@\n@.@@@.@@@@\n.@@@@@@@@.\n@.@.@@@.@.\n";
+
     #[test]
     fn test_visualize_sample() {
         visualize(
@@ -444,4 +577,90 @@ mod tests {
         )
         .unwrap();
     }
+
+    #[test]
+    fn test_visualize_inline_sample() {
+        let input = viz::write_temp("p04_inline_sample.txt", SAMPLE);
+        let input = input.to_str().unwrap();
+
+        let states = simulate_evolution(input, 100);
+        let frame = create_frame(&states[0], 3);
+        assert_eq!(frame.dimensions(), (30, 30));
+        assert_eq!(frame.get_pixel(0, 0), &COLOR_GRID);
+        assert_eq!(frame.get_pixel(1, 1), &COLOR_EMPTY);
+        // The top-left rock at (0, 2) has three neighbours: (0, 3), (1, 1) and
+        // (1, 2), too few to survive.
+        assert_eq!(frame.get_pixel(7, 1), &COLOR_ROCK_DANGER);
+
+        let gif = viz::temp_path("p04_sample.gif");
+        let summary = viz::temp_path("p04_sample.png");
+        visualize(input, gif.to_str(), summary.to_str(), 4, 10, 100).unwrap();
+        let mut decoder = gif::DecodeOptions::new()
+            .read_info(std::fs::File::open(&gif).unwrap())
+            .unwrap();
+        let mut frames = 0;
+        while decoder.read_next_frame().unwrap().is_some() {
+            frames += 1;
+        }
+        assert_eq!(frames, states.len());
+        assert!(image::open(&summary).is_ok());
+
+        // Later frames only carry the cells that were removed.
+        let mut decoder = gif::DecodeOptions::new()
+            .read_info(std::fs::File::open(&gif).unwrap())
+            .unwrap();
+        let first = decoder.read_next_frame().unwrap().unwrap();
+        assert_eq!(
+            (first.width, first.height, first.transparent),
+            (40, 40, None)
+        );
+        let second = decoder.read_next_frame().unwrap().unwrap().clone();
+        assert!(second.width * second.height < 40 * 40);
+        assert_eq!(second.transparent, Some(palette().len() as u8));
+        assert_eq!(second.dispose, gif::DisposalMethod::Keep);
+    }
+
+    #[test]
+    fn test_streaming_matches_buffered() {
+        let input = viz::write_temp("p04_streaming_sample.txt", SAMPLE);
+        let input = input.to_str().unwrap();
+
+        let states = simulate_evolution(input, 100);
+        let history = History::from_states(&states);
+        assert_eq!(history.len(), states.len());
+        let iterations = summary_indices(states.len());
+        for (replayed, state) in history.states_at(&iterations).iter().zip(&iterations) {
+            let state = &states[*state];
+            assert_eq!(replayed.iteration, state.iteration);
+            assert_eq!(replayed.grid, state.grid);
+            assert_eq!(replayed.neighbor_counts, state.neighbor_counts);
+            assert_eq!(replayed.cells_to_remove, state.cells_to_remove);
+        }
+
+        let outputs: Vec<(Vec<u8>, Vec<u8>)> = [Pipeline::Buffered, Pipeline::Streaming]
+            .into_iter()
+            .map(|pipeline| {
+                let gif = viz::temp_path(&format!("p04_{:?}.gif", pipeline));
+                let summary = viz::temp_path(&format!("p04_{:?}.png", pipeline));
+                visualize_with(
+                    input,
+                    gif.to_str(),
+                    summary.to_str(),
+                    4,
+                    10,
+                    100,
+                    pipeline,
+                    true,
+                )
+                .unwrap();
+                (std::fs::read(gif).unwrap(), std::fs::read(summary).unwrap())
+            })
+            .collect();
+        assert!(outputs[0] == outputs[1]);
+
+        // Stopping early still keeps the last state's removals.
+        let short = evolve(read_input(input), 2, |_| Ok(())).unwrap();
+        assert_eq!(short.len(), 2);
+        assert_eq!(short.rock_counts(), vec![71, 58]);
+    }
 }
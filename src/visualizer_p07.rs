@@ -0,0 +1,264 @@
+// Beam path renderer for problem 7: an ASCII overlay of the beams on the
+// manifold, and a PNG/GIF heatmap coloured by timeline multiplicity.
+
+use crate::bigint::BigUint;
+use crate::p_07::{Engine, Manifold, beam_multiplicities};
+use crate::viz::{self, CellStyle, FrameSource, GifMode, Palette};
+use image::{Rgb, RgbImage};
+use std::fs::read_to_string;
+
+// Color palette
+const COLOR_EMPTY: Rgb<u8> = Rgb([10, 10, 10]); // Near black
+const COLOR_SPLITTER: Rgb<u8> = Rgb([230, 230, 230]); // Light gray
+const COLOR_START: Rgb<u8> = Rgb([68, 255, 68]); // Bright green
+
+// Palette indices; heat levels follow the fixed colors.
+const INDEX_EMPTY: u8 = 0;
+const INDEX_SPLITTER: u8 = 1;
+const INDEX_START: u8 = 2;
+const INDEX_HEAT: u8 = 3;
+const HEAT_LEVELS: usize = 250;
+
+type Grid = Vec<Vec<char>>;
+
+fn read_input(text: &str) -> Grid {
+    text.lines().map(|line| line.chars().collect()).collect()
+}
+
+/// Draws `|` on every empty cell a beam passes through.
+fn render_ascii(grid: &Grid, multiplicities: &[Vec<BigUint>]) -> String {
+    let mut out = String::new();
+    for (i, row) in grid.iter().enumerate() {
+        for (j, ch) in row.iter().enumerate() {
+            if *ch == '.' && !multiplicities[i][j].is_zero() {
+                out.push('|');
+            } else {
+                out.push(*ch);
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Dark blue -> cyan -> yellow -> red as `t` goes from 0 to 1.
+fn heat_color(t: f64) -> Rgb<u8> {
+    let stops = [
+        (0.0, [30.0, 40.0, 160.0]),
+        (0.35, [0.0, 200.0, 220.0]),
+        (0.7, [255.0, 220.0, 0.0]),
+        (1.0, [255.0, 40.0, 40.0]),
+    ];
+    let t = t.clamp(0.0, 1.0);
+    for pair in stops.windows(2) {
+        let (t0, c0) = pair[0];
+        let (t1, c1) = pair[1];
+        if t <= t1 {
+            let f = (t - t0) / (t1 - t0);
+            let mix = |k: usize| (c0[k] + (c1[k] - c0[k]) * f).round() as u8;
+            return Rgb([mix(0), mix(1), mix(2)]);
+        }
+    }
+    Rgb([255, 40, 40])
+}
+
+fn palette() -> Palette {
+    let mut palette = Palette::new();
+    palette.add(COLOR_EMPTY); // Index 0
+    palette.add(COLOR_SPLITTER); // Index 1
+    palette.add(COLOR_START); // Index 2
+    for level in 0..HEAT_LEVELS {
+        palette.add(heat_color(level as f64 / (HEAT_LEVELS - 1) as f64));
+    }
+    palette
+}
+
+/// Palette index for every cell. Multiplicities are placed on a log scale
+/// since they double at every split.
+fn cell_indices(grid: &Grid, multiplicities: &[Vec<BigUint>]) -> Vec<Vec<u8>> {
+    let max_bits = multiplicities
+        .iter()
+        .flatten()
+        .map(|n| n.bits())
+        .max()
+        .unwrap_or(0);
+    let span = max_bits.saturating_sub(1).max(1);
+
+    grid.iter()
+        .enumerate()
+        .map(|(i, row)| {
+            (0..multiplicities[i].len())
+                .map(|j| match row.get(j) {
+                    Some('^') => INDEX_SPLITTER,
+                    Some('S') => INDEX_START,
+                    _ if multiplicities[i][j].is_zero() => INDEX_EMPTY,
+                    _ => {
+                        let level =
+                            (multiplicities[i][j].bits() - 1) * (HEAT_LEVELS as u64 - 1) / span;
+                        INDEX_HEAT + level as u8
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// One frame per row, revealing the beams as they travel down: frame `f`
+/// shows no beams below row `f`.
+struct Beams {
+    indices: Vec<Vec<u8>>,
+}
+
+impl FrameSource for Beams {
+    fn grid_size(&self) -> (usize, usize) {
+        (
+            self.indices.len(),
+            self.indices.first().map_or(0, |r| r.len()),
+        )
+    }
+
+    fn frame_count(&self) -> usize {
+        self.indices.len()
+    }
+
+    fn cell(&self, frame: usize, row: usize, col: usize) -> u8 {
+        match self.indices[row][col] {
+            idx if row > frame && idx >= INDEX_HEAT => INDEX_EMPTY,
+            idx => idx,
+        }
+    }
+}
+
+fn create_heatmap(beams: &Beams, cell_size: u32) -> RgbImage {
+    let last = beams.frame_count().saturating_sub(1);
+    viz::render_rgb(beams, last, CellStyle::new(cell_size), &palette())
+}
+
+fn create_gif_animation(
+    beams: &Beams,
+    cell_size: u32,
+    output_path: &str,
+    frame_delay: u16,
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!(
+        "Creating GIF animation with {} frames...",
+        beams.frame_count()
+    );
+    let report = viz::write_gif(
+        beams,
+        CellStyle::new(cell_size),
+        &palette(),
+        output_path,
+        frame_delay,
+        GifMode::Delta,
+    )?;
+    println!("GIF animation saved to {}: {}", output_path, report);
+    Ok(())
+}
+
+pub fn visualize(
+    input_path: &str,
+    output_png: Option<&str>,
+    output_gif: Option<&str>,
+    cell_size: u32,
+    frame_delay: u16,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let text = read_to_string(input_path)?;
+    let grid = read_input(&text);
+    let manifold = Manifold::parse(&text);
+    let multiplicities = beam_multiplicities(&manifold, Engine::Dense);
+
+    print!("{}", render_ascii(&grid, &multiplicities));
+
+    let beams = Beams {
+        indices: cell_indices(&grid, &multiplicities),
+    };
+    if let Some(png_path) = output_png {
+        create_heatmap(&beams, cell_size).save(png_path)?;
+        println!("Heatmap saved to {}", png_path);
+    }
+
+    if let Some(gif_path) = output_gif {
+        create_gif_animation(&beams, cell_size, gif_path, frame_delay)?;
+    }
+
+    println!("\n✓ Visualization complete!");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::p_07::SAMPLE;
+
+    #[test]
+    fn test_render_ascii() {
+        let grid = read_input(SAMPLE);
+        let multiplicities = beam_multiplicities(&Manifold::parse(SAMPLE), Engine::Sparse);
+        let expected = "\
+.......S.......
+.......|.......
+......|^|......
+......|.|......
+.....|^|^|.....
+.....|.|.|.....
+....|^|^|^|....
+....|.|.|.|....
+...|^|^|||^|...
+...|.|.|||.|...
+..|^|^|||^|^|..
+..|.|.|||.|.|..
+.|^|||^||.||^|.
+.|.|||.||.||.|.
+|^|^|^|^|^|||^|
+|.|.|.|.|.|||.|
+";
+        assert_eq!(render_ascii(&grid, &multiplicities), expected);
+    }
+
+    #[test]
+    fn test_heat_levels() {
+        let grid = read_input(SAMPLE);
+        let multiplicities = beam_multiplicities(&Manifold::parse(SAMPLE), Engine::Dense);
+        let indices = cell_indices(&grid, &multiplicities);
+        assert_eq!(indices[0][7], INDEX_START);
+        assert_eq!(indices[2][7], INDEX_SPLITTER);
+        // A single timeline is the coolest heat level.
+        assert_eq!(indices[1][7], INDEX_HEAT);
+        assert_eq!(indices[1][0], INDEX_EMPTY);
+        let hottest = indices.iter().flatten().copied().max().unwrap();
+        assert_eq!(hottest, INDEX_HEAT + HEAT_LEVELS as u8 - 1);
+
+        let beams = Beams { indices };
+        assert_eq!(beams.cell(0, 1, 7), INDEX_EMPTY);
+        assert_eq!(beams.cell(0, 2, 7), INDEX_SPLITTER);
+        assert_eq!(beams.cell(1, 1, 7), INDEX_HEAT);
+    }
+
+    #[test]
+    fn test_visualize_sample() {
+        let input = viz::write_temp("p_07_viz.txt", SAMPLE);
+        let png = viz::temp_path("p_07_heatmap.png");
+        let gif = viz::temp_path("p_07_beams.gif");
+        visualize(input.to_str().unwrap(), png.to_str(), gif.to_str(), 10, 20).unwrap();
+
+        let img = image::open(&png).unwrap();
+        assert_eq!((img.width(), img.height()), (150, 160));
+        assert!(std::fs::metadata(&gif).unwrap().len() > 0);
+    }
+
+    #[test]
+    fn test_oversized_gif_is_rejected() {
+        // 7000 columns at 10 pixels each don't fit a GIF's 16-bit width.
+        let mut text = ".".repeat(7000);
+        text.replace_range(3500..3501, "S");
+        text.push_str(&format!("\n{}\n", ".".repeat(7000)));
+        let grid = read_input(&text);
+        let multiplicities = beam_multiplicities(&Manifold::parse(&text), Engine::Sparse);
+        let beams = Beams {
+            indices: cell_indices(&grid, &multiplicities),
+        };
+        let gif = viz::temp_path("p_07_oversized.gif");
+        assert!(create_gif_animation(&beams, 10, gif.to_str().unwrap(), 5).is_err());
+    }
+}
@@ -0,0 +1,368 @@
+// Junction box renderer for problem 8: the point cloud projected to 2D and
+// coloured by cluster, with the minimum spanning tree drawn as lines, and a
+// GIF of Kruskal's algorithm merging clusters one edge at a time.
+
+use crate::p_08::{MstStrategy, Pos, SquaredEuclidean, parse_points, spanning_tree};
+use crate::viz::{Canvas, GifMode, GifWriter, Palette};
+use image::{Rgb, RgbImage};
+use std::fs::read_to_string;
+
+// Color palette
+const COLOR_BACKGROUND: Rgb<u8> = Rgb([10, 10, 10]); // Near black
+const COLOR_EDGE: Rgb<u8> = Rgb([90, 90, 110]); // Slate
+const COLOR_SINGLETON: Rgb<u8> = Rgb([170, 170, 170]); // Gray
+
+// Palette indices; cluster colors follow the fixed ones.
+const INDEX_BACKGROUND: u8 = 0;
+const INDEX_EDGE: u8 = 1;
+const INDEX_SINGLETON: u8 = 2;
+const INDEX_CLUSTER: u8 = 3;
+const CLUSTER_COLORS: usize = 64;
+
+const POINT_RADIUS: i64 = 2;
+const MARGIN: u32 = 10;
+
+/// How the 3D cloud is flattened onto the image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Projection {
+    /// Looking down the z axis.
+    Top,
+    /// Looking along the y axis.
+    Front,
+    /// Looking along the x axis.
+    Side,
+    /// All three axes at 120 degrees to each other.
+    #[default]
+    Isometric,
+}
+
+impl Projection {
+    /// Image-space coordinates, with `y` growing downwards.
+    fn project(self, p: &Pos) -> (f64, f64) {
+        let (x, y, z) = (p.axis(0) as f64, p.axis(1) as f64, p.axis(2) as f64);
+        match self {
+            Projection::Top => (x, -y),
+            Projection::Front => (x, -z),
+            Projection::Side => (y, -z),
+            Projection::Isometric => {
+                let (cos, sin) = (30f64.to_radians().cos(), 30f64.to_radians().sin());
+                ((x - y) * cos, (x + y) * sin - z)
+            }
+        }
+    }
+}
+
+/// Pixel positions of every point, scaled uniformly to fit a `size` square.
+fn layout(nodes: &[Pos], projection: Projection, size: u32) -> Vec<(i64, i64)> {
+    let projected: Vec<(f64, f64)> = nodes.iter().map(|p| projection.project(p)).collect();
+    let (mut min_x, mut min_y) = (f64::INFINITY, f64::INFINITY);
+    let (mut max_x, mut max_y) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+    for &(x, y) in &projected {
+        min_x = min_x.min(x);
+        min_y = min_y.min(y);
+        max_x = max_x.max(x);
+        max_y = max_y.max(y);
+    }
+    let usable = size.saturating_sub(2 * MARGIN).max(1) as f64 - 1.0;
+    let span = (max_x - min_x).max(max_y - min_y);
+    let scale = if span > 0.0 { usable / span } else { 0.0 };
+    projected
+        .iter()
+        .map(|&(x, y)| {
+            (
+                MARGIN as i64 + ((x - min_x) * scale).round() as i64,
+                MARGIN as i64 + ((y - min_y) * scale).round() as i64,
+            )
+        })
+        .collect()
+}
+
+/// Hue spread by the golden ratio so neighbouring labels look different.
+fn cluster_color(i: usize) -> Rgb<u8> {
+    let hue = (i as f64 * 0.618_033_988_75).fract() * 6.0;
+    let (s, v) = (0.75, 0.95);
+    let c = v * s;
+    let x = c * (1.0 - (hue % 2.0 - 1.0).abs());
+    let (r, g, b) = match hue as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let m = v - c;
+    let channel = |f: f64| ((f + m) * 255.0).round() as u8;
+    Rgb([channel(r), channel(g), channel(b)])
+}
+
+fn palette() -> Palette {
+    let mut palette = Palette::new();
+    palette.add(COLOR_BACKGROUND); // Index 0
+    palette.add(COLOR_EDGE); // Index 1
+    palette.add(COLOR_SINGLETON); // Index 2
+    for i in 0..CLUSTER_COLORS {
+        palette.add(cluster_color(i));
+    }
+    palette
+}
+
+/// Cluster membership while Kruskal's edges are applied one at a time. The
+/// smaller cluster always takes the larger one's label, so big clusters keep
+/// their color for the whole animation.
+struct KruskalReplay {
+    labels: Vec<usize>,
+    members: Vec<Vec<usize>>,
+}
+
+impl KruskalReplay {
+    fn new(n: usize) -> Self {
+        Self {
+            labels: (0..n).collect(),
+            members: (0..n).map(|i| vec![i]).collect(),
+        }
+    }
+
+    fn merge(&mut self, u: usize, v: usize) {
+        let (mut keep, mut absorb) = (self.labels[u], self.labels[v]);
+        if keep == absorb {
+            return;
+        }
+        if self.members[keep].len() < self.members[absorb].len() {
+            std::mem::swap(&mut keep, &mut absorb);
+        }
+        let moved = std::mem::take(&mut self.members[absorb]);
+        for &i in &moved {
+            self.labels[i] = keep;
+        }
+        self.members[keep].extend(moved);
+    }
+
+    fn index(&self, i: usize) -> u8 {
+        let label = self.labels[i];
+        if self.members[label].len() < 2 {
+            INDEX_SINGLETON
+        } else {
+            INDEX_CLUSTER + (label % CLUSTER_COLORS) as u8
+        }
+    }
+}
+
+/// The given edges as lines, then every point on top in its cluster color.
+fn draw_frame(
+    pixels: &[(i64, i64)],
+    edges: &[(u128, usize, usize)],
+    replay: &KruskalReplay,
+    size: u32,
+) -> Canvas {
+    let mut canvas = Canvas::new(size, size, INDEX_BACKGROUND);
+    for &(_, u, v) in edges {
+        canvas.line(pixels[u], pixels[v], INDEX_EDGE);
+    }
+    for (i, &pixel) in pixels.iter().enumerate() {
+        canvas.square(pixel, POINT_RADIUS, replay.index(i));
+    }
+    canvas
+}
+
+/// The whole tree, with points colored by the `clusters` groups left after
+/// Kruskal has joined everything else.
+fn create_snapshot(
+    pixels: &[(i64, i64)],
+    edges: &[(u128, usize, usize)],
+    clusters: usize,
+    size: u32,
+) -> RgbImage {
+    let mut replay = KruskalReplay::new(pixels.len());
+    let merges = pixels.len().saturating_sub(clusters.max(1));
+    for &(_, u, v) in edges.iter().take(merges) {
+        replay.merge(u, v);
+    }
+    draw_frame(pixels, edges, &replay, size).to_image(&palette())
+}
+
+/// One frame per group of merges, so that long runs stay under `max_frames`.
+fn create_gif_animation(
+    pixels: &[(i64, i64)],
+    edges: &[(u128, usize, usize)],
+    size: u16,
+    output_path: &str,
+    frame_delay: u16,
+    max_frames: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let step = edges.len().div_ceil(max_frames.max(2) - 1).max(1);
+    let mut cuts: Vec<usize> = (0..edges.len()).step_by(step).collect();
+    cuts.push(edges.len());
+
+    println!("Creating GIF animation with {} frames...", cuts.len());
+    let size = u32::from(size);
+    let mut writer = GifWriter::create_with(
+        output_path,
+        size,
+        size,
+        &palette(),
+        frame_delay,
+        GifMode::Delta,
+    )?;
+
+    let mut replay = KruskalReplay::new(pixels.len());
+    let mut applied = 0;
+    for &cut in &cuts {
+        for &(_, u, v) in &edges[applied..cut] {
+            replay.merge(u, v);
+        }
+        applied = cut;
+
+        writer.write_indexed(draw_frame(pixels, &edges[..cut], &replay, size).into_pixels())?;
+    }
+
+    println!(
+        "GIF animation saved to {}: {}",
+        output_path,
+        writer.finish()?
+    );
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn visualize(
+    input_path: &str,
+    projection: Projection,
+    output_png: Option<&str>,
+    output_gif: Option<&str>,
+    size: u32,
+    clusters: usize,
+    frame_delay: u16,
+    max_frames: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // GIF dimensions are 16-bit, and the PNG gets the same square.
+    let side = u16::try_from(size)?;
+    let nodes: Vec<Pos> = parse_points(&read_to_string(input_path)?)?;
+    let tree = spanning_tree(&nodes, &SquaredEuclidean, MstStrategy::Kruskal)?;
+    let pixels = layout(&nodes, projection, size);
+    println!(
+        "{} junction boxes, {} tree edges, total length {}",
+        nodes.len(),
+        tree.edges().len(),
+        tree.total()
+    );
+
+    if let Some(png_path) = output_png {
+        create_snapshot(&pixels, tree.edges(), clusters, size).save(png_path)?;
+        println!("Cluster image saved to {}", png_path);
+    }
+
+    if let Some(gif_path) = output_gif {
+        create_gif_animation(
+            &pixels,
+            tree.edges(),
+            side,
+            gif_path,
+            frame_delay,
+            max_frames,
+        )?;
+    }
+
+    println!("\n✓ Visualization complete!");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::p_08::SAMPLE;
+    use crate::viz;
+
+    #[test]
+    fn test_projection_and_layout() {
+        let nodes = [Pos::new(0, 0, 0), Pos::new(10, 0, 0), Pos::new(0, 5, 7)];
+        assert_eq!(Projection::Top.project(&nodes[2]), (0.0, -5.0));
+        assert_eq!(Projection::Front.project(&nodes[2]), (0.0, -7.0));
+        assert_eq!(Projection::Side.project(&nodes[2]), (5.0, -7.0));
+
+        // The longer x span fills the usable width, y keeps the same scale.
+        let pixels = layout(&nodes, Projection::Top, 120);
+        assert_eq!(pixels, vec![(10, 60), (109, 60), (10, 10)]);
+        for projection in [Projection::Front, Projection::Side, Projection::Isometric] {
+            for (x, y) in layout(&nodes, projection, 64) {
+                assert!((10..54).contains(&x) && (10..54).contains(&y));
+            }
+        }
+        assert_eq!(
+            layout(&nodes[..1], Projection::Isometric, 50),
+            vec![(10, 10)]
+        );
+    }
+
+    #[test]
+    fn test_replay_keeps_large_labels() {
+        let mut replay = KruskalReplay::new(5);
+        assert_eq!(replay.index(0), INDEX_SINGLETON);
+        replay.merge(3, 4);
+        replay.merge(4, 2);
+        replay.merge(0, 2);
+        assert_eq!(replay.labels, vec![3, 1, 3, 3, 3]);
+        assert_eq!(replay.index(0), INDEX_CLUSTER + 3);
+        assert_eq!(replay.index(1), INDEX_SINGLETON);
+        replay.merge(0, 4);
+        assert_eq!(replay.members[3].len(), 4);
+    }
+
+    #[test]
+    fn test_visualize_sample() {
+        let input = viz::write_temp("p_08_viz.txt", SAMPLE);
+        let png = viz::temp_path("p_08_clusters.png");
+        let gif = viz::temp_path("p_08_kruskal.gif");
+        visualize(
+            input.to_str().unwrap(),
+            Projection::Isometric,
+            png.to_str(),
+            gif.to_str(),
+            200,
+            4,
+            20,
+            8,
+        )
+        .unwrap();
+
+        let img = image::open(&png).unwrap();
+        assert_eq!((img.width(), img.height()), (200, 200));
+
+        let mut decoder = gif::DecodeOptions::new()
+            .read_info(std::fs::File::open(&gif).unwrap())
+            .unwrap();
+        let mut frames = 0;
+        while decoder.read_next_frame().unwrap().is_some() {
+            frames += 1;
+        }
+        // 19 merges in steps of three, plus the finished tree.
+        assert_eq!(frames, 8);
+    }
+
+    #[test]
+    fn test_visualize_rejects_bad_input() {
+        let ragged = viz::write_temp("p_08_viz_ragged.txt", "1,2,3\n4,5\n");
+        let sample = viz::write_temp("p_08_viz_oversized.txt", SAMPLE);
+        let run = |input: &std::path::Path, size| {
+            visualize(
+                input.to_str().unwrap(),
+                Projection::Top,
+                None,
+                None,
+                size,
+                4,
+                20,
+                8,
+            )
+        };
+
+        assert_eq!(
+            run(&ragged, 200).unwrap_err().to_string(),
+            "line 2: expected 3 coordinates, found 2"
+        );
+
+        // Too wide for a GIF, refused before anything is drawn.
+        assert!(run(&sample, 70_000).is_err());
+        assert!(run(&sample, u32::MAX).is_err());
+    }
+}
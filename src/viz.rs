@@ -0,0 +1,734 @@
+// Building blocks shared by the visualizers: a palette, indexed frames of
+// square cells or freely drawn pixels, and PNG/GIF output. A grid day only
+// has to say which palette index every cell has in every frame.
+
+use gif::{DisposalMethod, Encoder, Frame, Repeat};
+use image::{ImageBuffer, Rgb, RgbImage};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Write};
+
+pub type VizResult<T> = Result<T, Box<dyn std::error::Error>>;
+
+/// Up to 256 colors, addressed by the index GIF frames store per pixel.
+#[derive(Debug, Clone, Default)]
+pub struct Palette {
+    colors: Vec<Rgb<u8>>,
+}
+
+impl Palette {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends `color` and returns its index.
+    pub fn add(&mut self, color: Rgb<u8>) -> u8 {
+        assert!(
+            self.colors.len() < 256,
+            "GIF palettes hold at most 256 colors"
+        );
+        self.colors.push(color);
+        (self.colors.len() - 1) as u8
+    }
+
+    pub fn color(&self, index: u8) -> Rgb<u8> {
+        self.colors[index as usize]
+    }
+
+    pub fn len(&self) -> usize {
+        self.colors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.colors.is_empty()
+    }
+
+    /// Flat RGB bytes padded with black to 256 entries.
+    pub fn to_gif_palette(&self) -> Vec<u8> {
+        let mut palette: Vec<u8> = self.colors.iter().flat_map(|c| c.0).collect();
+
+        // Pad palette to 256 colors (GIF requirement)
+        palette.resize(256 * 3, 0);
+        palette
+    }
+}
+
+/// A sequence of frames over a fixed grid of cells.
+pub trait FrameSource {
+    /// Grid size as `(rows, columns)`.
+    fn grid_size(&self) -> (usize, usize);
+
+    fn frame_count(&self) -> usize;
+
+    /// Palette index of one cell in one frame.
+    fn cell(&self, frame: usize, row: usize, col: usize) -> u8;
+}
+
+/// How cells become pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CellStyle {
+    pub cell_size: u32,
+    /// Color of a one pixel line along the top and left of every cell. Only
+    /// drawn when cells are bigger than two pixels.
+    pub grid_line: Option<u8>,
+}
+
+impl CellStyle {
+    pub fn new(cell_size: u32) -> Self {
+        Self {
+            cell_size,
+            grid_line: None,
+        }
+    }
+
+    pub fn with_grid_line(mut self, index: u8) -> Self {
+        self.grid_line = Some(index);
+        self
+    }
+}
+
+/// Frame size in pixels as `(width, height)`.
+pub fn frame_size<S: FrameSource + ?Sized>(source: &S, style: CellStyle) -> (u32, u32) {
+    let (rows, cols) = source.grid_size();
+    (cols as u32 * style.cell_size, rows as u32 * style.cell_size)
+}
+
+/// Row-major palette indices for one frame.
+pub fn render_indexed<S: FrameSource + ?Sized>(
+    source: &S,
+    frame: usize,
+    style: CellStyle,
+) -> Vec<u8> {
+    let (rows, cols) = source.grid_size();
+    let size = style.cell_size as usize;
+    let grid_line = style.grid_line.filter(|_| style.cell_size > 2);
+    let mut pixels = Vec::with_capacity(rows * cols * size * size);
+    let mut line = Vec::with_capacity(cols * size);
+    for row in 0..rows {
+        line.clear();
+        for col in 0..cols {
+            let idx = source.cell(frame, row, col);
+            line.push(grid_line.unwrap_or(idx));
+            line.extend(std::iter::repeat_n(idx, size.saturating_sub(1)));
+        }
+        for dy in 0..size {
+            match grid_line {
+                Some(grid) if dy == 0 => pixels.extend(std::iter::repeat_n(grid, line.len())),
+                _ => pixels.extend_from_slice(&line),
+            }
+        }
+    }
+    pixels
+}
+
+pub fn indexed_to_rgb(pixels: &[u8], width: u32, height: u32, palette: &Palette) -> RgbImage {
+    let mut img = ImageBuffer::new(width, height);
+    for (pixel, &idx) in img.pixels_mut().zip(pixels.iter()) {
+        *pixel = palette.color(idx);
+    }
+    img
+}
+
+pub fn render_rgb<S: FrameSource + ?Sized>(
+    source: &S,
+    frame: usize,
+    style: CellStyle,
+    palette: &Palette,
+) -> RgbImage {
+    let (width, height) = frame_size(source, style);
+    indexed_to_rgb(
+        &render_indexed(source, frame, style),
+        width,
+        height,
+        palette,
+    )
+}
+
+/// Palette indices drawn pixel by pixel, for pictures that are not a grid of
+/// cells. Anything drawn outside is clipped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    pub fn new(width: u32, height: u32, background: u8) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![background; width as usize * height as usize],
+        }
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> u8 {
+        self.pixels[y as usize * self.width as usize + x as usize]
+    }
+
+    pub fn put(&mut self, x: i64, y: i64, index: u8) {
+        if (0..self.width as i64).contains(&x) && (0..self.height as i64).contains(&y) {
+            self.pixels[y as usize * self.width as usize + x as usize] = index;
+        }
+    }
+
+    /// Bresenham line including both end points.
+    pub fn line(&mut self, (x0, y0): (i64, i64), (x1, y1): (i64, i64), index: u8) {
+        let (dx, dy) = ((x1 - x0).abs(), -(y1 - y0).abs());
+        let (sx, sy) = (if x0 < x1 { 1 } else { -1 }, if y0 < y1 { 1 } else { -1 });
+        let (mut x, mut y, mut err) = (x0, y0, dx + dy);
+        loop {
+            self.put(x, y, index);
+            if x == x1 && y == y1 {
+                break;
+            }
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y += sy;
+            }
+        }
+    }
+
+    /// The square of side `2 * radius + 1` centred on `(x, y)`.
+    pub fn square(&mut self, (x, y): (i64, i64), radius: i64, index: u8) {
+        for dy in -radius..=radius {
+            for dx in -radius..=radius {
+                self.put(x + dx, y + dy, index);
+            }
+        }
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn into_pixels(self) -> Vec<u8> {
+        self.pixels
+    }
+
+    pub fn to_image(&self, palette: &Palette) -> RgbImage {
+        indexed_to_rgb(&self.pixels, self.width, self.height, palette)
+    }
+}
+
+/// How `GifWriter` stores each frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GifMode {
+    /// Every frame is a full, opaque image.
+    Full,
+    /// After the first frame, only the bounding box of the pixels that
+    /// changed is stored. Unchanged pixels inside it use a spare palette
+    /// index marked transparent, and frames are kept rather than disposed so
+    /// each one draws on top of the last.
+    #[default]
+    Delta,
+}
+
+/// Passes writes through while counting the bytes.
+struct CountingWriter<W> {
+    inner: W,
+    bytes: u64,
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.bytes += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+fn looping_encoder<W: Write>(
+    inner: W,
+    width: u16,
+    height: u16,
+    palette: &[u8],
+) -> VizResult<Encoder<CountingWriter<W>>> {
+    let writer = CountingWriter { inner, bytes: 0 };
+    let mut encoder = Encoder::new(writer, width, height, palette)?;
+    encoder.set_repeat(Repeat::Infinite)?;
+    Ok(encoder)
+}
+
+/// State kept by `GifMode::Delta`: the previous frame to diff against.
+struct Delta {
+    transparent: u8,
+    previous: Option<Vec<u8>>,
+}
+
+/// Size of a finished GIF.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GifReport {
+    pub frames: usize,
+    pub bytes: u64,
+    /// What the same frames take when every one is stored in full, if
+    /// `GifWriter::with_full_frame_comparison` asked for it.
+    pub full_frame_bytes: Option<u64>,
+}
+
+impl fmt::Display for GifReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} frames, {} bytes", self.frames, self.bytes)?;
+        if let Some(full) = self.full_frame_bytes.filter(|&full| full != self.bytes) {
+            let saved = full as f64 - self.bytes as f64;
+            write!(
+                f,
+                " (full frames: {} bytes, {:.1}% smaller)",
+                full,
+                100.0 * saved / full as f64
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Looping GIF written one frame at a time, so callers never have to hold
+/// more than the frame they are drawing.
+pub struct GifWriter {
+    encoder: Encoder<CountingWriter<File>>,
+    width: u16,
+    height: u16,
+    delay: u16,
+    frames: usize,
+    delta: Option<Delta>,
+    /// Full-frame encoding of the same frames, only measured.
+    comparison: Option<Encoder<CountingWriter<io::Sink>>>,
+}
+
+impl GifWriter {
+    pub fn create(
+        path: &str,
+        width: u32,
+        height: u32,
+        palette: &Palette,
+        delay: u16,
+    ) -> VizResult<Self> {
+        Self::create_with(path, width, height, palette, delay, GifMode::default())
+    }
+
+    /// Like `create`. `GifMode::Delta` needs a free palette slot for the
+    /// transparent index.
+    pub fn create_with(
+        path: &str,
+        width: u32,
+        height: u32,
+        palette: &Palette,
+        delay: u16,
+        mode: GifMode,
+    ) -> VizResult<Self> {
+        let (width, height) = (u16::try_from(width)?, u16::try_from(height)?);
+        let colors = palette.to_gif_palette();
+        let delta = match mode {
+            GifMode::Full => None,
+            GifMode::Delta => {
+                let transparent = u8::try_from(palette.len())
+                    .map_err(|_| "delta GIFs need a free palette index for transparency")?;
+                Some(Delta {
+                    transparent,
+                    previous: None,
+                })
+            }
+        };
+        Ok(Self {
+            encoder: looping_encoder(File::create(path)?, width, height, &colors)?,
+            width,
+            height,
+            delay,
+            frames: 0,
+            delta,
+            comparison: None,
+        })
+    }
+
+    /// Also encodes every delta frame in full, without keeping the result,
+    /// so `finish` can report the saving. This doubles the encoding work.
+    /// Has no effect on `GifMode::Full`, which already stores full frames.
+    pub fn with_full_frame_comparison(mut self, palette: &Palette) -> VizResult<Self> {
+        assert_eq!(self.frames, 0, "the comparison has to see every frame");
+        if self.delta.is_some() {
+            let colors = palette.to_gif_palette();
+            self.comparison = Some(looping_encoder(
+                io::sink(),
+                self.width,
+                self.height,
+                &colors,
+            )?);
+        }
+        Ok(self)
+    }
+
+    fn full_frame(&self, pixels: Vec<u8>) -> Frame<'static> {
+        let mut frame = Frame::default();
+        frame.width = self.width;
+        frame.height = self.height;
+        frame.delay = self.delay;
+        frame.buffer = std::borrow::Cow::Owned(pixels);
+        frame
+    }
+
+    /// Appends a full frame of `width * height` palette indices.
+    pub fn write_indexed(&mut self, pixels: Vec<u8>) -> VizResult<()> {
+        assert_eq!(pixels.len(), self.width as usize * self.height as usize);
+        let full = self.full_frame(pixels);
+        let Some(delta) = self.delta.as_mut() else {
+            self.encoder.write_frame(&full)?;
+            self.frames += 1;
+            return Ok(());
+        };
+
+        if let Some(comparison) = self.comparison.as_mut() {
+            comparison.write_frame(&full)?;
+        }
+        let mut frame = match &delta.previous {
+            Some(previous) => changed_region(previous, &full, delta.transparent),
+            None => full.clone(),
+        };
+        frame.delay = self.delay;
+        frame.dispose = DisposalMethod::Keep;
+        self.encoder.write_frame(&frame)?;
+        delta.previous = Some(full.buffer.into_owned());
+        self.frames += 1;
+        Ok(())
+    }
+
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+
+    /// Writes the trailer and reports the file size.
+    pub fn finish(self) -> VizResult<GifReport> {
+        let bytes = self.encoder.into_inner()?.bytes;
+        let full_frame_bytes = match (self.delta, self.comparison) {
+            (None, _) => Some(bytes),
+            (Some(_), Some(comparison)) => Some(comparison.into_inner()?.bytes),
+            (Some(_), None) => None,
+        };
+        Ok(GifReport {
+            frames: self.frames,
+            bytes,
+            full_frame_bytes,
+        })
+    }
+}
+
+/// The smallest frame that turns `previous` into `current` when drawn on top
+/// of it. Pixels that did not change are `transparent`.
+fn changed_region(previous: &[u8], current: &Frame, transparent: u8) -> Frame<'static> {
+    let width = current.width as usize;
+    let mut bounds: Option<(usize, usize, usize, usize)> = None;
+    for (i, (a, b)) in previous.iter().zip(current.buffer.iter()).enumerate() {
+        if a != b {
+            let (x, y) = (i % width, i / width);
+            bounds = Some(match bounds {
+                None => (x, y, x, y),
+                Some((left, top, right, bottom)) => {
+                    (left.min(x), top.min(y), right.max(x), bottom.max(y))
+                }
+            });
+        }
+    }
+
+    // An unchanged frame still needs to be written to keep its delay.
+    let Some((left, top, right, bottom)) = bounds else {
+        return Frame::from_indexed_pixels(1, 1, vec![transparent], Some(transparent));
+    };
+
+    let mut pixels = Vec::with_capacity((right - left + 1) * (bottom - top + 1));
+    for y in top..=bottom {
+        let row = y * width + left..=y * width + right;
+        for (&a, &b) in previous[row.clone()].iter().zip(&current.buffer[row]) {
+            pixels.push(if a == b { transparent } else { b });
+        }
+    }
+    let mut frame = Frame::from_indexed_pixels(
+        (right - left + 1) as u16,
+        (bottom - top + 1) as u16,
+        pixels,
+        Some(transparent),
+    );
+    frame.left = left as u16;
+    frame.top = top as u16;
+    frame
+}
+
+/// Every frame of `source` as a GIF.
+pub fn write_gif<S: FrameSource + ?Sized>(
+    source: &S,
+    style: CellStyle,
+    palette: &Palette,
+    path: &str,
+    delay: u16,
+    mode: GifMode,
+) -> VizResult<GifReport> {
+    let (width, height) = frame_size(source, style);
+    let mut writer = GifWriter::create_with(path, width, height, palette, delay, mode)?;
+    for frame in 0..source.frame_count() {
+        writer.write_indexed(render_indexed(source, frame, style))?;
+    }
+    writer.finish()
+}
+
+/// Lays equally sized images out left to right, top to bottom, `cols` per
+/// row, with `padding` pixels of `background` around each.
+pub fn tile_images(images: &[RgbImage], cols: u32, padding: u32, background: Rgb<u8>) -> RgbImage {
+    let (frame_width, frame_height) = images.first().map_or((0, 0), |img| img.dimensions());
+    let rows = (images.len() as u32).div_ceil(cols);
+
+    let total_width = cols * frame_width + (cols + 1) * padding;
+    let total_height = rows * frame_height + (rows + 1) * padding;
+    let mut tiled = ImageBuffer::from_pixel(total_width, total_height, background);
+
+    for (idx, img) in images.iter().enumerate() {
+        let row = idx as u32 / cols;
+        let col = idx as u32 % cols;
+        let x_offset = (col + 1) * padding + col * frame_width;
+        let y_offset = (row + 1) * padding + row * frame_height;
+        for (x, y, pixel) in img.enumerate_pixels() {
+            tiled.put_pixel(x_offset + x, y_offset + y, *pixel);
+        }
+    }
+    tiled
+}
+
+/// A path under the temp directory for test files. The process id keeps
+/// parallel test runs apart; `name` has to be unique within a run.
+#[cfg(test)]
+pub(crate) fn temp_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("aoc2025_{}_{}", std::process::id(), name))
+}
+
+/// Writes `text` to `temp_path(name)`, typically a puzzle input for a
+/// visualizer to read back.
+#[cfg(test)]
+pub(crate) fn write_temp(name: &str, text: &str) -> std::path::PathBuf {
+    let path = temp_path(name);
+    std::fs::write(&path, text).unwrap();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 2x3 grid where frame `f` lights up cell `f`.
+    struct Walker;
+
+    impl FrameSource for Walker {
+        fn grid_size(&self) -> (usize, usize) {
+            (2, 3)
+        }
+
+        fn frame_count(&self) -> usize {
+            6
+        }
+
+        fn cell(&self, frame: usize, row: usize, col: usize) -> u8 {
+            (row * 3 + col == frame) as u8
+        }
+    }
+
+    fn palette() -> Palette {
+        let mut palette = Palette::new();
+        assert_eq!(palette.add(Rgb([0, 0, 0])), 0);
+        assert_eq!(palette.add(Rgb([255, 255, 255])), 1);
+        assert_eq!(palette.add(Rgb([50, 50, 50])), 2);
+        palette
+    }
+
+    #[test]
+    fn test_palette() {
+        let palette = palette();
+        assert_eq!(palette.len(), 3);
+        assert_eq!(palette.color(1), Rgb([255, 255, 255]));
+        let bytes = palette.to_gif_palette();
+        assert_eq!(bytes.len(), 768);
+        assert_eq!(&bytes[3..9], &[255, 255, 255, 50, 50, 50]);
+        assert!(bytes[9..].iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn test_render_cells() {
+        assert_eq!(frame_size(&Walker, CellStyle::new(2)), (6, 4));
+        assert_eq!(
+            render_indexed(&Walker, 4, CellStyle::new(2)),
+            vec![
+                0, 0, 0, 0, 0, 0, //
+                0, 0, 0, 0, 0, 0, //
+                0, 0, 1, 1, 0, 0, //
+                0, 0, 1, 1, 0, 0, //
+            ]
+        );
+        // Grid lines only show once cells are big enough to spare a pixel.
+        assert_eq!(
+            render_indexed(&Walker, 4, CellStyle::new(2).with_grid_line(2)),
+            render_indexed(&Walker, 4, CellStyle::new(2))
+        );
+        let lined = render_indexed(&Walker, 0, CellStyle::new(3).with_grid_line(2));
+        assert_eq!(&lined[..9], &[2; 9]);
+        assert_eq!(&lined[9..18], &[2, 1, 1, 2, 0, 0, 2, 0, 0]);
+        let img = render_rgb(&Walker, 0, CellStyle::new(3).with_grid_line(2), &palette());
+        assert_eq!(img.get_pixel(1, 1), &Rgb([255, 255, 255]));
+        assert_eq!(img.get_pixel(0, 1), &Rgb([50, 50, 50]));
+    }
+
+    /// A `rows` x `cols` grid where frame `f` has its first `f` cells lit.
+    struct Fill {
+        rows: usize,
+        cols: usize,
+    }
+
+    impl FrameSource for Fill {
+        fn grid_size(&self) -> (usize, usize) {
+            (self.rows, self.cols)
+        }
+
+        fn frame_count(&self) -> usize {
+            self.rows * self.cols + 2
+        }
+
+        fn cell(&self, frame: usize, row: usize, col: usize) -> u8 {
+            (row * self.cols + col < frame) as u8
+        }
+    }
+
+    /// Decodes `path`, drawing every frame over the previous ones the way
+    /// `DisposalMethod::Keep` asks.
+    fn composited_frames(path: &std::path::Path) -> Vec<Vec<u8>> {
+        let mut decoder = gif::DecodeOptions::new()
+            .read_info(File::open(path).unwrap())
+            .unwrap();
+        let width = decoder.width() as usize;
+        let mut canvas = vec![0; width * decoder.height() as usize];
+        let mut frames = Vec::new();
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            for (i, &idx) in frame.buffer.iter().enumerate() {
+                if Some(idx) != frame.transparent {
+                    let x = frame.left as usize + i % frame.width as usize;
+                    let y = frame.top as usize + i / frame.width as usize;
+                    canvas[y * width + x] = idx;
+                }
+            }
+            frames.push(canvas.clone());
+        }
+        frames
+    }
+
+    #[test]
+    fn test_delta_frames() {
+        let source = Fill { rows: 20, cols: 20 };
+        let style = CellStyle::new(4).with_grid_line(2);
+        let mut reports = Vec::new();
+        for mode in [GifMode::Full, GifMode::Delta] {
+            let path = temp_path(&format!("viz_fill_{:?}.gif", mode));
+            let report =
+                write_gif(&source, style, &palette(), path.to_str().unwrap(), 5, mode).unwrap();
+            assert_eq!(report.bytes, std::fs::metadata(&path).unwrap().len());
+            let frames = composited_frames(&path);
+            assert_eq!(frames.len(), source.frame_count());
+            for (f, frame) in frames.iter().enumerate() {
+                assert_eq!(frame, &render_indexed(&source, f, style), "frame {}", f);
+            }
+            reports.push(report);
+        }
+
+        let (full, delta) = (reports[0], reports[1]);
+        assert_eq!(full.full_frame_bytes, Some(full.bytes));
+        assert_eq!(delta.full_frame_bytes, None);
+        assert!(delta.bytes * 2 < full.bytes, "{}", delta);
+        assert!(!delta.to_string().contains("% smaller"));
+
+        // Measuring the saving is opt-in and matches a real full-frame file.
+        let (width, height) = frame_size(&source, style);
+        let path = temp_path("viz_fill_compared.gif");
+        let mut writer = GifWriter::create(path.to_str().unwrap(), width, height, &palette(), 5)
+            .unwrap()
+            .with_full_frame_comparison(&palette())
+            .unwrap();
+        for f in 0..source.frame_count() {
+            writer
+                .write_indexed(render_indexed(&source, f, style))
+                .unwrap();
+        }
+        let compared = writer.finish().unwrap();
+        assert_eq!(compared.bytes, delta.bytes);
+        assert_eq!(compared.full_frame_bytes, Some(full.bytes));
+        assert!(compared.to_string().contains("% smaller"));
+
+        // The transparent index has to come from a free palette slot.
+        let mut crowded = Palette::new();
+        for i in 0..=255 {
+            crowded.add(Rgb([i, i, i]));
+        }
+        let path = temp_path("viz_crowded.gif");
+        let path = path.to_str().unwrap();
+        assert!(GifWriter::create_with(path, 4, 4, &crowded, 5, GifMode::Delta).is_err());
+        assert!(GifWriter::create_with(path, 4, 4, &crowded, 5, GifMode::Full).is_ok());
+    }
+
+    #[test]
+    fn test_canvas() {
+        let mut canvas = Canvas::new(5, 3, 0);
+        canvas.line((0, 0), (4, 2), 1);
+        assert_eq!(
+            canvas.pixels(),
+            &[
+                1, 0, 0, 0, 0, //
+                0, 1, 1, 0, 0, //
+                0, 0, 0, 1, 1, //
+            ]
+        );
+        // Clipped at the edges rather than wrapping onto the next row.
+        canvas.square((4, 0), 1, 2);
+        assert_eq!(canvas.get(3, 0), 2);
+        assert_eq!(canvas.get(4, 1), 2);
+        assert_eq!(canvas.get(0, 1), 0);
+        assert_eq!(canvas.pixels().iter().filter(|&&p| p == 2).count(), 4);
+        let img = canvas.to_image(&palette());
+        assert_eq!(img.dimensions(), (5, 3));
+        assert_eq!(img.get_pixel(2, 1), &Rgb([255, 255, 255]));
+        assert_eq!(img.get_pixel(4, 0), &Rgb([50, 50, 50]));
+    }
+
+    #[test]
+    fn test_write_gif_and_tiles() {
+        let path = temp_path("viz_walker.gif");
+        let report = write_gif(
+            &Walker,
+            CellStyle::new(4),
+            &palette(),
+            path.to_str().unwrap(),
+            5,
+            GifMode::Full,
+        )
+        .unwrap();
+        assert_eq!(report.frames, 6);
+        let mut decoder = gif::DecodeOptions::new()
+            .read_info(File::open(&path).unwrap())
+            .unwrap();
+        let mut decoded = 0;
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!((frame.width, frame.height, frame.delay), (12, 8, 5));
+            decoded += 1;
+        }
+        assert_eq!(decoded, 6);
+
+        let images: Vec<RgbImage> = (0..5)
+            .map(|f| render_rgb(&Walker, f, CellStyle::new(1), &palette()))
+            .collect();
+        let tiled = tile_images(&images, 3, 2, Rgb([9, 9, 9]));
+        assert_eq!(tiled.dimensions(), (3 * 3 + 4 * 2, 2 * 2 + 3 * 2));
+        assert_eq!(tiled.get_pixel(0, 0), &Rgb([9, 9, 9]));
+        // Frame 1 lights its second cell, in the second tile of the first row.
+        assert_eq!(tiled.get_pixel(2 + 3 + 2 + 1, 2), &Rgb([255, 255, 255]));
+        assert_eq!(tiled.get_pixel(16, 9), &Rgb([9, 9, 9]));
+    }
+}